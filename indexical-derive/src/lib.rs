@@ -0,0 +1,54 @@
+//! Derive macros for [indexical](https://docs.rs/indexical).
+//!
+//! Use these through the `derive` feature of `indexical` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, Path};
+
+/// Implements `IndexedValue` for a type, given an existing index type.
+///
+/// The index type is provided with the `#[indexed_value(index = ...)]` attribute,
+/// and must implement [`index_vec::Idx`](https://docs.rs/index_vec/latest/index_vec/trait.Idx.html).
+/// See the `indexical::IndexedValue` docs for an example.
+#[proc_macro_derive(IndexedValue, attributes(indexed_value))]
+pub fn derive_indexed_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut index: Option<Path> = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("indexed_value"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("index") {
+                index = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `index = ...`"))
+            }
+        })?;
+    }
+
+    let Some(index) = index else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "missing `#[indexed_value(index = ...)]` attribute",
+        ));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::indexical::IndexedValue for #name #ty_generics #where_clause {
+            type Index = #index;
+        }
+    })
+}
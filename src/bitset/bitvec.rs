@@ -1,6 +1,10 @@
 //! A bit-set from the [`bitvec`] crate.
 
-use bitvec::{prelude::Lsb0, slice::IterOnes};
+use bitvec::{field::BitField, prelude::Lsb0, slice::IterOnes};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
 
 use crate::{
     bitset::BitSet,
@@ -26,10 +30,36 @@ impl BitSet for BitVec {
         !contained
     }
 
+    fn remove(&mut self, index: usize) -> bool {
+        let contained = self[index];
+        self.set(index, false);
+        contained
+    }
+
+    fn any_in_range(&self, range: Range<usize>) -> bool {
+        self[range].any()
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         self.iter_ones()
     }
 
+    fn iter_rev(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.iter_ones().rev())
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.first_one()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.last_one()
+    }
+
+    fn rank(&self, index: usize) -> usize {
+        self[..index.min(BitVec::len(self))].count_ones()
+    }
+
     fn len(&self) -> usize {
         self.count_ones()
     }
@@ -47,7 +77,8 @@ impl BitSet for BitVec {
     }
 
     fn clear(&mut self) {
-        self.clear();
+        // Note: `BitVec::clear` truncates the vector, but we need to keep the domain size.
+        self.fill(false);
     }
 
     fn subtract(&mut self, other: &Self) {
@@ -56,13 +87,47 @@ impl BitSet for BitVec {
         self.intersect(&other_copy);
     }
 
+    fn symmetric_difference(&mut self, other: &Self) {
+        *self ^= other;
+    }
+
     fn insert_all(&mut self) {
         self.fill(true);
     }
 
+    fn count_intersection(&self, other: &Self) -> usize {
+        // As in `hash_words`, loading each chunk ignores the dead bits past the end.
+        self.chunks(usize::BITS as usize)
+            .zip(other.chunks(usize::BITS as usize))
+            .map(|(a, b)| (a.load_le::<usize>() & b.load_le::<usize>()).count_ones() as usize)
+            .sum()
+    }
+
     fn copy_from(&mut self, other: &Self) {
         self.copy_from_bitslice(other);
     }
+
+    fn hash_words<H: Hasher>(&self, state: &mut H) {
+        // Loading each chunk ignores the dead bits past the end of the vector,
+        // which may be set by `invert`.
+        for chunk in self.chunks(usize::BITS as usize) {
+            chunk.load_le::<usize>().hash(state);
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        BitVec::shrink_to_fit(self);
+    }
+
+    fn grow(&mut self, new_size: usize) {
+        debug_assert!(new_size >= BitVec::len(self));
+        self.resize(new_size, false);
+    }
+
+    fn truncate(&mut self, new_size: usize) {
+        debug_assert!(new_size <= BitVec::len(self));
+        BitVec::truncate(self, new_size);
+    }
 }
 
 /// [`IndexSet`](crate::IndexSet) specialized to the [`BitVec`] implementation.
@@ -86,4 +151,20 @@ pub type RefIndexMatrix<'a, R, C> = crate::IndexMatrix<'a, R, C, BitVec, RefFami
 #[test]
 fn test_bitvec() {
     crate::test_utils::impl_test::<BitVec>();
+
+    // Inverting sets the dead bits past the end, which must not affect the hash.
+    let hash = |bv: &BitVec| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        bv.hash_words(&mut hasher);
+        hasher.finish()
+    };
+    let mut bv = BitVec::empty(10);
+    BitSet::insert(&mut bv, 3);
+    let mut bv2 = BitVec::empty(10);
+    for i in (0..10).filter(|i| *i != 3) {
+        BitSet::insert(&mut bv2, i);
+    }
+    bv2.invert();
+    assert_eq!(bv, bv2);
+    assert_eq!(hash(&bv), hash(&bv2));
 }
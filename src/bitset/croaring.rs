@@ -0,0 +1,186 @@
+//! A bit-set based on the C implementation of roaring bitmaps, [`Bitmap`].
+//!
+//! This backend links against CRoaring, which can be faster than the pure-Rust
+//! [`roaring`](super::roaring) backend for some workloads.
+
+pub use croaring::{self, Bitmap};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use crate::{
+    bitset::BitSet,
+    pointer::{ArcFamily, RcFamily, RefFamily},
+};
+
+/// Wrapper around a [`Bitmap`] that includes the domain size.
+#[derive(PartialEq, Eq, Clone)]
+pub struct CRoaringSet {
+    set: Bitmap,
+    size: usize,
+}
+
+impl Hash for CRoaringSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.size.hash(state);
+        self.hash_words(state);
+    }
+}
+
+fn to_usize(i: u32) -> usize {
+    i as usize
+}
+
+impl BitSet for CRoaringSet {
+    type Iter<'a> = std::iter::Map<croaring::bitmap::BitmapIterator<'a>, fn(i: u32) -> usize>;
+
+    fn empty(size: usize) -> Self {
+        CRoaringSet {
+            set: Bitmap::new(),
+            size,
+        }
+    }
+
+    fn insert(&mut self, index: usize) -> bool {
+        self.set.add_checked(index as u32)
+    }
+
+    fn remove(&mut self, index: usize) -> bool {
+        self.set.remove_checked(index as u32)
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.set.contains(index as u32)
+    }
+
+    fn any_in_range(&self, range: Range<usize>) -> bool {
+        self.set
+            .range_cardinality((range.start as u32)..(range.end as u32))
+            > 0
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.set.iter().map(to_usize)
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.set.minimum().map(to_usize)
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.set.maximum().map(to_usize)
+    }
+
+    fn nth(&self, n: usize) -> Option<usize> {
+        self.set.select(u32::try_from(n).ok()?).map(to_usize)
+    }
+
+    fn rank(&self, index: usize) -> usize {
+        match index.checked_sub(1) {
+            Some(max) => self.set.rank(max as u32) as usize,
+            None => 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.set.cardinality() as usize
+    }
+
+    fn union(&mut self, other: &Self) {
+        self.set.or_inplace(&other.set);
+    }
+
+    fn intersect(&mut self, other: &Self) {
+        self.set.and_inplace(&other.set);
+    }
+
+    fn subtract(&mut self, other: &Self) {
+        self.set.andnot_inplace(&other.set);
+    }
+
+    fn symmetric_difference(&mut self, other: &Self) {
+        self.set.xor_inplace(&other.set);
+    }
+
+    fn invert(&mut self) {
+        self.set.flip_inplace(0..(self.size as u32));
+    }
+
+    fn clear(&mut self) {
+        self.set.clear();
+    }
+
+    fn insert_all(&mut self) {
+        self.set.add_range(0..(self.size as u32));
+    }
+
+    fn superset(&self, other: &Self) -> bool {
+        other.set.is_subset(&self.set)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        !self.set.intersect(&other.set)
+    }
+
+    fn count_intersection(&self, other: &Self) -> usize {
+        self.set.and_cardinality(&other.set) as usize
+    }
+
+    fn count_union(&self, other: &Self) -> usize {
+        self.set.or_cardinality(&other.set) as usize
+    }
+
+    fn count_symmetric_difference(&self, other: &Self) -> usize {
+        self.set.xor_cardinality(&other.set) as usize
+    }
+
+    fn copy_from(&mut self, other: &Self) {
+        self.set.clone_from(&other.set);
+    }
+
+    fn grow(&mut self, new_size: usize) {
+        debug_assert!(new_size >= self.size);
+        self.size = new_size;
+    }
+
+    fn truncate(&mut self, new_size: usize) {
+        debug_assert!(new_size <= self.size);
+        self.set.remove_range((new_size as u32)..);
+        self.size = new_size;
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.set.shrink_to_fit();
+    }
+}
+
+/// [`IndexSet`](crate::IndexSet) specialized to the [`CRoaringSet`] implementation.
+pub type IndexSet<T> = crate::IndexSet<'static, T, CRoaringSet, RcFamily>;
+
+/// [`IndexSet`](crate::IndexSet) specialized to the [`CRoaringSet`] implementation with the [`ArcFamily`].
+pub type ArcIndexSet<'a, T> = crate::IndexSet<'a, T, CRoaringSet, ArcFamily>;
+
+/// [`IndexSet`](crate::IndexSet) specialized to the [`CRoaringSet`] implementation with the [`RefFamily`].
+pub type RefIndexSet<'a, T> = crate::IndexSet<'a, T, CRoaringSet, RefFamily<'a>>;
+
+/// [`IndexMatrix`](crate::IndexMatrix) specialized to the [`CRoaringSet`] implementation.
+pub type IndexMatrix<R, C> = crate::IndexMatrix<'static, R, C, CRoaringSet, RcFamily>;
+
+/// [`IndexMatrix`](crate::IndexMatrix) specialized to the [`CRoaringSet`] implementation with the [`ArcFamily`].
+pub type ArcIndexMatrix<R, C> = crate::IndexMatrix<'static, R, C, CRoaringSet, ArcFamily>;
+
+/// [`IndexMatrix`](crate::IndexMatrix) specialized to the [`CRoaringSet`] implementation with the [`RefFamily`].
+pub type RefIndexMatrix<'a, R, C> = crate::IndexMatrix<'a, R, C, CRoaringSet, RefFamily<'a>>;
+
+#[test]
+fn test_croaring() {
+    crate::test_utils::impl_test::<CRoaringSet>();
+
+    let mut a = CRoaringSet::empty(10);
+    a.insert(3);
+    let mut b = CRoaringSet::empty(10);
+    b.insert(3);
+    let sets = std::collections::HashSet::from([a, b, CRoaringSet::empty(10)]);
+    assert_eq!(sets.len(), 2);
+}
@@ -1,5 +1,10 @@
 //! Abstraction over bit-set implementations.
 
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
 /// Interface for bit-set implementations.
 ///
 /// Implement this trait if you want to provide a custom bit-set
@@ -16,12 +21,59 @@ pub trait BitSet: Clone + PartialEq {
     /// Sets `index` to 1, returning true if `self` changed.
     fn insert(&mut self, index: usize) -> bool;
 
+    /// Sets `index` to 0, returning true if `self` changed.
+    fn remove(&mut self, index: usize) -> bool {
+        if !self.contains(index) {
+            return false;
+        }
+        let mut mask = self.clone();
+        mask.clear();
+        mask.insert(index);
+        self.subtract(&mask);
+        true
+    }
+
     /// Returns true if `index` is 1.
     fn contains(&self, index: usize) -> bool;
 
+    /// Returns true if any index in `range` is 1.
+    ///
+    /// `range` must lie within the domain of the bit-set.
+    fn any_in_range(&self, range: Range<usize>) -> bool {
+        range.into_iter().any(|index| self.contains(index))
+    }
+
     /// Returns an iterator over all the indices of ones in the bit-set.
     fn iter(&self) -> Self::Iter<'_>;
 
+    /// Returns an iterator over all the indices of ones in the bit-set, in descending order.
+    ///
+    /// The default implementation collects [`BitSet::iter`] into a vector,
+    /// so backends with double-ended iterators should override this.
+    fn iter_rev(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.iter().collect::<Vec<_>>().into_iter().rev())
+    }
+
+    /// Returns the smallest index of a one in the bit-set, if any.
+    fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    /// Returns the largest index of a one in the bit-set, if any.
+    fn last(&self) -> Option<usize> {
+        self.iter().last()
+    }
+
+    /// Returns the index of the `n`-th one in the bit-set (counting from zero), if any.
+    fn nth(&self, n: usize) -> Option<usize> {
+        self.iter().nth(n)
+    }
+
+    /// Returns the number of ones at indices strictly less than `index`.
+    fn rank(&self, index: usize) -> usize {
+        self.iter().take_while(|i| *i < index).count()
+    }
+
     /// Returns the number of ones in the bit-set.
     fn len(&self) -> usize;
 
@@ -60,10 +112,27 @@ pub trait BitSet: Clone + PartialEq {
     /// Removes all ones from `other` in `self`, returning true if `self` changed.
     fn subtract_changed(&mut self, other: &Self) -> bool {
         let n = self.len();
-        self.intersect(other);
+        self.subtract(other);
         n != self.len()
     }
 
+    /// Keeps the ones in exactly one of `self` or `other`.
+    fn symmetric_difference(&mut self, other: &Self) {
+        let mut common = self.clone();
+        common.intersect(other);
+        self.union(other);
+        self.subtract(&common);
+    }
+
+    /// Keeps the ones in exactly one of `self` or `other`, returning true if `self` changed.
+    fn symmetric_difference_changed(&mut self, other: &Self) -> bool {
+        // Unlike the other `_changed` methods, comparing lengths is not enough here,
+        // since e.g. {0} ^ {0, 1} = {1}. Instead, `self` changes iff `other` has any ones.
+        let changed = !other.is_empty();
+        self.symmetric_difference(other);
+        changed
+    }
+
     /// Flips all bits in `self`.
     fn invert(&mut self);
 
@@ -75,15 +144,86 @@ pub trait BitSet: Clone + PartialEq {
 
     /// Returns true if all ones in `other` are a one in `self`.
     fn superset(&self, other: &Self) -> bool {
-        let orig_len = self.len();
-        // TODO: can we avoid this clone?
-        let mut self_copy = self.clone();
-        self_copy.union(other);
-        orig_len == self_copy.len()
+        other.iter().all(|index| self.contains(index))
+    }
+
+    /// Returns true if `self` and `other` have no ones in common.
+    fn is_disjoint(&self, other: &Self) -> bool {
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        !smaller.iter().any(|index| larger.contains(index))
+    }
+
+    /// Returns the number of ones in both `self` and `other`, without modifying either.
+    ///
+    /// The default implementation iterates over the smaller set.
+    fn count_intersection(&self, other: &Self) -> usize {
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        smaller
+            .iter()
+            .filter(|index| larger.contains(*index))
+            .count()
+    }
+
+    /// Returns the number of ones in either `self` or `other`, without modifying either.
+    fn count_union(&self, other: &Self) -> usize {
+        self.len() + other.len() - self.count_intersection(other)
+    }
+
+    /// Returns the number of ones in exactly one of `self` or `other`, without modifying either.
+    fn count_symmetric_difference(&self, other: &Self) -> usize {
+        self.len() + other.len() - 2 * self.count_intersection(other)
     }
 
     /// Copies `other` into `self`. Must have the same lengths.
     fn copy_from(&mut self, other: &Self);
+
+    /// Feeds the contents of `self` into `state`.
+    ///
+    /// Dense backends override this to hash their raw words, so hashes are
+    /// only consistent between bit-sets of the same backend. The default
+    /// implementation hashes each index of a one.
+    fn hash_words<H: Hasher>(&self, state: &mut H) {
+        for index in self.iter() {
+            index.hash(state);
+        }
+    }
+
+    /// Shrinks the memory used by `self` as much as possible.
+    ///
+    /// The default implementation does nothing.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Extends the domain of `self` to `new_size`, where every new index is 0.
+    ///
+    /// `new_size` must be at least the current domain size. The default implementation
+    /// copies the ones of `self` into a fresh bit-set.
+    fn grow(&mut self, new_size: usize) {
+        let mut set = Self::empty(new_size);
+        for index in self.iter() {
+            set.insert(index);
+        }
+        *self = set;
+    }
+
+    /// Shrinks the domain of `self` to `new_size`, dropping any ones at or past `new_size`.
+    ///
+    /// `new_size` must be at most the current domain size. The default implementation
+    /// copies the remaining ones of `self` into a fresh bit-set.
+    fn truncate(&mut self, new_size: usize) {
+        let mut set = Self::empty(new_size);
+        for index in self.iter().take_while(|index| *index < new_size) {
+            set.insert(index);
+        }
+        *self = set;
+    }
 }
 
 #[cfg(feature = "bitvec")]
@@ -97,3 +237,6 @@ pub mod simd;
 
 #[cfg(feature = "roaring")]
 pub mod roaring;
+
+#[cfg(feature = "croaring")]
+pub mod croaring;
@@ -4,6 +4,10 @@
 //! your indexical feature list.
 
 pub use roaring::{self, RoaringBitmap};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
 
 use crate::{
     bitset::BitSet,
@@ -17,6 +21,16 @@ pub struct RoaringSet {
     size: usize,
 }
 
+// `RoaringBitmap` does not implement `Eq`, but its `PartialEq` is a full equivalence.
+impl Eq for RoaringSet {}
+
+impl Hash for RoaringSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.size.hash(state);
+        self.hash_words(state);
+    }
+}
+
 fn to_usize(i: u32) -> usize {
     i as usize
 }
@@ -35,14 +49,47 @@ impl BitSet for RoaringSet {
         self.set.insert(index as u32)
     }
 
+    fn remove(&mut self, index: usize) -> bool {
+        self.set.remove(index as u32)
+    }
+
     fn contains(&self, index: usize) -> bool {
         self.set.contains(index as u32)
     }
 
+    fn any_in_range(&self, range: Range<usize>) -> bool {
+        self.set
+            .range_cardinality((range.start as u32)..(range.end as u32))
+            > 0
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         self.set.iter().map(to_usize)
     }
 
+    fn iter_rev(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.set.iter().rev().map(to_usize))
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.set.min().map(to_usize)
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.set.max().map(to_usize)
+    }
+
+    fn nth(&self, n: usize) -> Option<usize> {
+        self.set.select(u32::try_from(n).ok()?).map(to_usize)
+    }
+
+    fn rank(&self, index: usize) -> usize {
+        match index.checked_sub(1) {
+            Some(max) => self.set.rank(max as u32) as usize,
+            None => 0,
+        }
+    }
+
     fn len(&self) -> usize {
         self.set.len() as usize
     }
@@ -59,6 +106,10 @@ impl BitSet for RoaringSet {
         self.set -= &other.set;
     }
 
+    fn symmetric_difference(&mut self, other: &Self) {
+        self.set ^= &other.set;
+    }
+
     fn invert(&mut self) {
         for i in 0..self.size {
             if self.set.contains(i as u32) {
@@ -77,9 +128,40 @@ impl BitSet for RoaringSet {
         self.set.insert_range(0..(self.size as u32));
     }
 
+    fn superset(&self, other: &Self) -> bool {
+        self.set.is_superset(&other.set)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.set.is_disjoint(&other.set)
+    }
+
+    fn count_intersection(&self, other: &Self) -> usize {
+        self.set.intersection_len(&other.set) as usize
+    }
+
+    fn count_union(&self, other: &Self) -> usize {
+        self.set.union_len(&other.set) as usize
+    }
+
+    fn count_symmetric_difference(&self, other: &Self) -> usize {
+        self.set.symmetric_difference_len(&other.set) as usize
+    }
+
     fn copy_from(&mut self, other: &Self) {
         self.set.clone_from(&other.set);
     }
+
+    fn grow(&mut self, new_size: usize) {
+        debug_assert!(new_size >= self.size);
+        self.size = new_size;
+    }
+
+    fn truncate(&mut self, new_size: usize) {
+        debug_assert!(new_size <= self.size);
+        self.set.remove_range((new_size as u32)..);
+        self.size = new_size;
+    }
 }
 
 /// [`IndexSet`](crate::IndexSet) specialized to the [`RoaringSet`] implementation.
@@ -103,4 +185,11 @@ pub type RefIndexMatrix<'a, R, C> = crate::IndexMatrix<'a, R, C, RoaringSet, Ref
 #[test]
 fn test_roaring() {
     crate::test_utils::impl_test::<RoaringSet>();
+
+    let mut a = RoaringSet::empty(10);
+    a.insert(3);
+    let mut b = RoaringSet::empty(10);
+    b.insert(3);
+    let sets = std::collections::HashSet::from([a, b, RoaringSet::empty(10)]);
+    assert_eq!(sets.len(), 2);
 }
@@ -7,10 +7,10 @@ extern crate rustc_mir_dataflow;
 use crate::{
     bitset::BitSet,
     pointer::{ArcFamily, PointerFamily, RcFamily, RefFamily},
-    IndexedValue,
+    IndexedValue, Join,
 };
 use rustc_mir_dataflow::JoinSemiLattice;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 
 pub use rustc_index::bit_set;
 
@@ -32,6 +32,10 @@ impl BitSet for RustcBitSet {
         self.insert(index)
     }
 
+    fn remove(&mut self, index: usize) -> bool {
+        self.remove(index)
+    }
+
     fn iter(&self) -> Self::Iter<'_> {
         self.iter()
     }
@@ -78,9 +82,24 @@ impl BitSet for RustcBitSet {
         self.insert_all();
     }
 
+    fn superset(&self, other: &Self) -> bool {
+        self.superset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.words()
+            .iter()
+            .zip(other.words())
+            .all(|(a, b)| a & b == 0)
+    }
+
     fn copy_from(&mut self, other: &Self) {
         self.clone_from(other);
     }
+
+    fn hash_words<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(self, state);
+    }
 }
 
 /// [`IndexSet`](crate::IndexSet) specialized to the `bit_set::BitSet` implementation.
@@ -108,7 +127,7 @@ where
     P: PointerFamily<'a>,
 {
     fn join(&mut self, other: &Self) -> bool {
-        self.union_changed(other)
+        Join::join(self, other)
     }
 }
 
@@ -120,11 +139,7 @@ where
     P: PointerFamily<'a>,
 {
     fn join(&mut self, other: &Self) -> bool {
-        let mut changed = false;
-        for (row, col) in other.matrix.iter() {
-            changed |= self.ensure_row(row.clone()).union_changed(col);
-        }
-        changed
+        Join::join(self, other)
     }
 }
 
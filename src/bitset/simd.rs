@@ -5,6 +5,10 @@
 //! The main difference is I made a much more efficient iterator that computes the indices
 //! of the 1-bits.
 //!
+//! The [`IndexSet`] and [`IndexMatrix`] aliases use 4 lanes of `u64` per chunk. To pick a different
+//! lane count, e.g. 2 lanes for small domains or 8 lanes for wide vector units, use the
+//! [`IndexSetN`] and [`IndexMatrixN`] aliases (or [`SimdBitset`] directly).
+//!
 //! **WARNING:** this module makes liberal use of unsafe code and has not been thoroughly vetted,
 //! so use it at your own risk.
 
@@ -13,8 +17,9 @@ use crate::{
     pointer::{ArcFamily, RcFamily, RefFamily},
 };
 use std::{
+    hash::{Hash, Hasher},
     mem::size_of,
-    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXorAssign, Not},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Range},
     simd::{LaneCount, Simd, SimdElement, SupportedLaneCount},
     slice,
 };
@@ -29,6 +34,7 @@ pub trait SimdSetElement:
     + BitXorAssign
     + BitAndAssign
     + PartialEq
+    + Hash
     + 'static
 {
     /// The `0` value.
@@ -55,6 +61,9 @@ pub trait SimdSetElement:
     /// The number of zeros before the first 1 bit, counting from LSB.
     fn trailing_zeros(self) -> u32;
 
+    /// The number of zeros after the last 1 bit, counting from LSB.
+    fn leading_zeros(self) -> u32;
+
     /// The number of 1 bits in the element.
     fn count_ones(self) -> u32;
 }
@@ -81,6 +90,11 @@ macro_rules! simd_set_element_impl {
                 <$n>::trailing_zeros(self)
             }
 
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                <$n>::leading_zeros(self)
+            }
+
             #[inline]
             fn count_ones(self) -> u32 {
                 <$n>::count_ones(self)
@@ -95,7 +109,7 @@ simd_set_element_impl!(u32);
 simd_set_element_impl!(u64);
 
 /// A dense bit-set with SIMD-accelerated operations.
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Eq, Hash, Clone)]
 pub struct SimdBitset<T, const N: usize>
 where
     T: SimdSetElement,
@@ -117,6 +131,37 @@ where
         size_of::<T>() * 8
     }
 
+    const fn n_chunks(nbits: usize) -> usize {
+        (nbits + Self::chunk_size() - 1) / Self::chunk_size()
+    }
+
+    /// Returns a mask of the bits within `nbits` for the lane starting at bit `lane_start`.
+    #[inline]
+    fn lane_mask(&self, lane_start: usize) -> T {
+        if lane_start >= self.nbits {
+            return T::ZERO;
+        }
+        let lane_size = Self::lane_size();
+        let nbits = (self.nbits - lane_start).min(lane_size);
+        // SAFETY: `0 < nbits <= lane_size`, so the shift is in range.
+        unsafe { T::MAX.unchecked_shr((lane_size - nbits) as u32) }
+    }
+
+    /// Zeroes the padding bits at or past `nbits` in the last chunk.
+    ///
+    /// Every operation that could set a padding bit calls this afterward, so the other
+    /// operations (e.g. `len`) can assume that all padding bits are 0.
+    fn mask_tail(&mut self) {
+        let Some(chunk_idx) = self.chunks.len().checked_sub(1) else {
+            return;
+        };
+        let start = chunk_idx * Self::chunk_size();
+        for lane_idx in 0..N {
+            let mask = self.lane_mask(start + lane_idx * Self::lane_size());
+            self.chunks[chunk_idx].as_mut_array()[lane_idx] &= mask;
+        }
+    }
+
     #[inline(always)]
     const fn coords(&self, index: usize) -> (usize, usize, u32) {
         let (chunk, index) = (index / Self::chunk_size(), index % Self::chunk_size());
@@ -176,9 +221,12 @@ where
     #[inline]
     fn new(set: &'a SimdBitset<T, N>) -> Self {
         let mut chunk_iter = set.chunks.iter();
-        let chunk = chunk_iter.next().unwrap();
-        let mut lane_iter = chunk.as_array().iter();
-        let lane = *lane_iter.next().unwrap();
+        // A set with `nbits == 0` has no chunks, so start from an empty lane.
+        let mut lane_iter = match chunk_iter.next() {
+            Some(chunk) => chunk.as_array().iter(),
+            None => [].iter(),
+        };
+        let lane = lane_iter.next().copied().unwrap_or(T::ZERO);
 
         SimdSetIter {
             set,
@@ -233,6 +281,7 @@ where
         let zeros = self.lane.trailing_zeros();
         let idx = self.index + zeros as usize;
         self.lane ^= unsafe { T::ONE.unchecked_shl(zeros) };
+        // Padding bits are always 0 (see `mask_tail`), but stop at `nbits` regardless.
         if idx >= self.set.nbits {
             self.index = self.set.nbits;
             return None;
@@ -246,14 +295,14 @@ where
     LaneCount<N>: SupportedLaneCount,
     Simd<T, N>: for<'a> BitOr<&'a Simd<T, N>, Output = Simd<T, N>>,
     Simd<T, N>: for<'a> BitAnd<&'a Simd<T, N>, Output = Simd<T, N>>,
+    Simd<T, N>: for<'a> BitXor<&'a Simd<T, N>, Output = Simd<T, N>>,
 {
     type Iter<'a> = SimdSetIter<'a, T, N>;
 
     #[inline]
     fn empty(nbits: usize) -> Self {
-        let n_chunks = (nbits + Self::chunk_size() - 1) / Self::chunk_size();
         SimdBitset {
-            chunks: vec![Simd::from([T::ZERO; N]); n_chunks],
+            chunks: vec![Simd::from([T::ZERO; N]); Self::n_chunks(nbits)],
             nbits,
         }
     }
@@ -269,10 +318,29 @@ where
         unsafe {
             let chunk = self.chunks.get_unchecked_mut(chunk_idx);
             let lane = chunk.as_mut_array().get_unchecked_mut(lane_idx);
-            *lane |= T::ONE.unchecked_shl(bit);
+            let mask = T::ONE.unchecked_shl(bit);
+            let contained = *lane & mask != T::ZERO;
+            *lane |= mask;
+            !contained
         }
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> bool {
+        let (chunk_idx, lane_idx, bit) = self.coords(index);
+
+        debug_assert!(chunk_idx < self.chunks.len());
+        debug_assert!(lane_idx < N);
+        debug_assert!(bit < Self::lane_size() as u32);
 
-        true
+        unsafe {
+            let chunk = self.chunks.get_unchecked_mut(chunk_idx);
+            let lane = chunk.as_mut_array().get_unchecked_mut(lane_idx);
+            let mask = T::ONE.unchecked_shl(bit);
+            let contained = *lane & mask != T::ZERO;
+            *lane &= !mask;
+            contained
+        }
     }
 
     #[inline]
@@ -281,11 +349,75 @@ where
         self.get(chunk_idx, lane_idx, bit)
     }
 
+    #[inline]
+    fn any_in_range(&self, range: Range<usize>) -> bool {
+        debug_assert!(range.end <= self.nbits);
+
+        let lane_size = Self::lane_size();
+        let mut index = range.start;
+        while index < range.end {
+            let (chunk_idx, lane_idx, bit) = self.coords(index);
+            let nbits = (lane_size - bit as usize).min(range.end - index);
+
+            // SAFETY: `0 < nbits <= lane_size - bit`, so both shifts are in range.
+            let (lane, mask) = unsafe {
+                let chunk = self.chunks.get_unchecked(chunk_idx);
+                let lane = *chunk.as_array().get_unchecked(lane_idx);
+                let mask = T::MAX
+                    .unchecked_shr((lane_size - nbits) as u32)
+                    .unchecked_shl(bit);
+                (lane, mask)
+            };
+            if lane & mask != T::ZERO {
+                return true;
+            }
+
+            index += nbits;
+        }
+        false
+    }
+
     #[inline]
     fn iter(&self) -> Self::Iter<'_> {
         SimdSetIter::new(self)
     }
 
+    #[inline]
+    fn first(&self) -> Option<usize> {
+        let lane_size = Self::lane_size();
+        let zero = Simd::splat(T::ZERO);
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
+            if *chunk == zero {
+                continue;
+            }
+            for (lane_idx, lane) in chunk.as_array().iter().enumerate() {
+                if *lane != T::ZERO {
+                    let start = (chunk_idx * N + lane_idx) * lane_size;
+                    return Some(start + lane.trailing_zeros() as usize);
+                }
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn last(&self) -> Option<usize> {
+        let lane_size = Self::lane_size();
+        let zero = Simd::splat(T::ZERO);
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate().rev() {
+            if *chunk == zero {
+                continue;
+            }
+            for (lane_idx, lane) in chunk.as_array().iter().enumerate().rev() {
+                if *lane != T::ZERO {
+                    let start = (chunk_idx * N + lane_idx) * lane_size;
+                    return Some(start + lane_size - 1 - lane.leading_zeros() as usize);
+                }
+            }
+        }
+        None
+    }
+
     #[inline]
     fn len(&self) -> usize {
         let mut n = 0;
@@ -314,6 +446,11 @@ where
         self.intersect(&other);
     }
 
+    #[inline]
+    fn symmetric_difference(&mut self, other: &Self) {
+        self.zip_mut(other, |dst, src| *dst ^= src);
+    }
+
     #[inline]
     fn invert(&mut self) {
         for chunk in self.chunks.iter_mut() {
@@ -321,6 +458,7 @@ where
                 *lane = !*lane;
             }
         }
+        self.mask_tail();
     }
 
     #[inline]
@@ -339,12 +477,101 @@ where
                 *lane = T::MAX;
             }
         }
+        self.mask_tail();
+    }
+
+    #[inline]
+    fn is_disjoint(&self, other: &Self) -> bool {
+        let zero = Simd::splat(T::ZERO);
+        self.chunks
+            .iter()
+            .zip(&other.chunks)
+            .all(|(a, b)| (*a & b) == zero)
+    }
+
+    #[inline]
+    fn count_intersection(&self, other: &Self) -> usize {
+        debug_assert!(other.chunks.len() == self.chunks.len());
+        let mut n = 0;
+        for (a, b) in self.chunks.iter().zip(&other.chunks) {
+            for lane in (*a & b).as_array() {
+                n += lane.count_ones();
+            }
+        }
+        n as usize
     }
 
     #[inline]
     fn copy_from(&mut self, other: &Self) {
         self.zip_mut(other, |dst, src| *dst = *src);
     }
+
+    #[inline]
+    fn hash_words<H: Hasher>(&self, state: &mut H) {
+        for chunk in &self.chunks {
+            chunk.as_array().hash(state);
+        }
+    }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.chunks.shrink_to_fit();
+    }
+
+    fn grow(&mut self, new_size: usize) {
+        debug_assert!(new_size >= self.nbits);
+        self.chunks
+            .resize(Self::n_chunks(new_size), Simd::splat(T::ZERO));
+        self.nbits = new_size;
+    }
+
+    fn truncate(&mut self, new_size: usize) {
+        debug_assert!(new_size <= self.nbits);
+        self.chunks.truncate(Self::n_chunks(new_size));
+        self.nbits = new_size;
+        self.mask_tail();
+    }
+}
+
+macro_rules! impl_simd_op {
+    ($op:ident, $op_fn:ident, $method:ident) => {
+        impl<T: SimdSetElement, const N: usize> $op for &SimdBitset<T, N>
+        where
+            LaneCount<N>: SupportedLaneCount,
+            Simd<T, N>: for<'a> BitOr<&'a Simd<T, N>, Output = Simd<T, N>>,
+            Simd<T, N>: for<'a> BitAnd<&'a Simd<T, N>, Output = Simd<T, N>>,
+            Simd<T, N>: for<'a> BitXor<&'a Simd<T, N>, Output = Simd<T, N>>,
+        {
+            type Output = SimdBitset<T, N>;
+
+            fn $op_fn(self, rhs: Self) -> Self::Output {
+                debug_assert_eq!(self.nbits, rhs.nbits, "bit-sets have different sizes");
+                let mut out = self.clone();
+                out.$method(rhs);
+                out
+            }
+        }
+    };
+}
+
+impl_simd_op!(BitOr, bitor, union);
+impl_simd_op!(BitAnd, bitand, intersect);
+impl_simd_op!(BitXor, bitxor, symmetric_difference);
+
+impl<T: SimdSetElement, const N: usize> Not for &SimdBitset<T, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+    Simd<T, N>: for<'a> BitOr<&'a Simd<T, N>, Output = Simd<T, N>>,
+    Simd<T, N>: for<'a> BitAnd<&'a Simd<T, N>, Output = Simd<T, N>>,
+    Simd<T, N>: for<'a> BitXor<&'a Simd<T, N>, Output = Simd<T, N>>,
+{
+    type Output = SimdBitset<T, N>;
+
+    fn not(self) -> Self::Output {
+        let mut out = self.clone();
+        out.invert();
+        out
+    }
 }
 
 /// [`IndexSet`](crate::IndexSet) specialized to the [`SimdBitset`] implementation.
@@ -365,6 +592,29 @@ pub type ArcIndexMatrix<R, C> = crate::IndexMatrix<'static, R, C, SimdBitset<u64
 /// [`IndexMatrix`](crate::IndexMatrix) specialized to the [`SimdBitset`] implementation with the [`RefFamily`].
 pub type RefIndexMatrix<'a, R, C> = crate::IndexMatrix<'a, R, C, SimdBitset<u64, 4>, RefFamily<'a>>;
 
+/// [`IndexSet`](crate::IndexSet) specialized to the [`SimdBitset`] implementation with `N` lanes of `u64`.
+pub type IndexSetN<T, const N: usize> = crate::IndexSet<'static, T, SimdBitset<u64, N>, RcFamily>;
+
+/// [`IndexSetN`] with the [`ArcFamily`].
+pub type ArcIndexSetN<'a, T, const N: usize> =
+    crate::IndexSet<'a, T, SimdBitset<u64, N>, ArcFamily>;
+
+/// [`IndexSetN`] with the [`RefFamily`].
+pub type RefIndexSetN<'a, T, const N: usize> =
+    crate::IndexSet<'a, T, SimdBitset<u64, N>, RefFamily<'a>>;
+
+/// [`IndexMatrix`](crate::IndexMatrix) specialized to the [`SimdBitset`] implementation with `N` lanes of `u64`.
+pub type IndexMatrixN<R, C, const N: usize> =
+    crate::IndexMatrix<'static, R, C, SimdBitset<u64, N>, RcFamily>;
+
+/// [`IndexMatrixN`] with the [`ArcFamily`].
+pub type ArcIndexMatrixN<R, C, const N: usize> =
+    crate::IndexMatrix<'static, R, C, SimdBitset<u64, N>, ArcFamily>;
+
+/// [`IndexMatrixN`] with the [`RefFamily`].
+pub type RefIndexMatrixN<'a, R, C, const N: usize> =
+    crate::IndexMatrix<'a, R, C, SimdBitset<u64, N>, RefFamily<'a>>;
+
 #[test]
 fn test_simd_bitset() {
     const N: usize = 64 * 7 + 63;
@@ -382,5 +632,89 @@ fn test_simd_bitset() {
         assert_eq!(bitset.len(), i);
     }
 
+    let mut bitset = SimdBitset::<u64, 4>::empty(N);
+    for i in [1, 3, 5, 63] {
+        bitset.insert(i);
+    }
+    assert!(bitset.insert(7));
+    assert!(!bitset.insert(7));
+    assert!(bitset.remove(7));
+    assert!(bitset.remove(3));
+    assert!(!bitset.remove(3));
+    assert_eq!(bitset.iter().collect::<Vec<_>>(), vec![1, 5, 63]);
+
     crate::test_utils::impl_test::<SimdBitset<u64, 4>>();
 }
+
+#[test]
+fn test_simd_bitset_ops() {
+    let set = |indices: &[usize]| {
+        let mut set = SimdBitset::<u64, 4>::empty(300);
+        for i in indices {
+            set.insert(*i);
+        }
+        set
+    };
+    let (a, b) = (set(&[1, 64, 299]), set(&[64, 100]));
+    let elems = |set: SimdBitset<u64, 4>| set.iter().collect::<Vec<_>>();
+    assert_eq!(elems(&a | &b), vec![1, 64, 100, 299]);
+    assert_eq!(elems(&a & &b), vec![64]);
+    assert_eq!(elems(&a ^ &b), vec![1, 100, 299]);
+    assert_eq!(
+        elems(!&a),
+        (0..300)
+            .filter(|i| ![1, 64, 299].contains(i))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_simd_lane_counts() {
+    use crate::{test_utils::StrIdx, IndexedDomain};
+    use std::rc::Rc;
+
+    let d = Rc::new(IndexedDomain::from_iter((0..1000).map(|i| i.to_string())));
+    let mut s = IndexSetN::<String, 8>::new(&d);
+    s.insert(StrIdx::from_usize(3));
+    s.insert(StrIdx::from_usize(999));
+    assert_eq!(s.iter().collect::<Vec<_>>(), vec!["3", "999"]);
+
+    let mut s2 = IndexSetN::<String, 2>::new(&d);
+    s2.insert(StrIdx::from_usize(999));
+    assert_eq!(s2.len(), 1);
+
+    crate::test_utils::impl_test::<SimdBitset<u64, 8>>();
+}
+
+#[test]
+fn test_simd_iter_padding() {
+    for nbits in [0, 1, 63, 64 * 4 + 1, 64 * 5 + 3] {
+        let mut bitset = SimdBitset::<u64, 4>::empty(nbits);
+        bitset.insert_all();
+        assert_eq!(
+            bitset.iter().collect::<Vec<_>>(),
+            (0..nbits).collect::<Vec<_>>()
+        );
+
+        bitset.invert();
+        assert_eq!(bitset.iter().count(), 0);
+        bitset.invert();
+        let mut iter = bitset.iter();
+        assert_eq!(iter.by_ref().count(), nbits);
+        assert_eq!(iter.next(), None);
+    }
+}
+
+#[test]
+fn test_simd_len_after_insert_all() {
+    let nbits = 64 * 4 + 1;
+    let mut bitset = SimdBitset::<u64, 4>::empty(nbits);
+    bitset.insert_all();
+    assert_eq!(bitset.len(), nbits);
+    assert_eq!(bitset.last(), Some(nbits - 1));
+
+    bitset.remove(0);
+    bitset.invert();
+    assert_eq!(bitset.len(), 1);
+    assert_eq!(bitset.iter().collect::<Vec<_>>(), vec![0]);
+}
@@ -1,12 +1,24 @@
 use ahash::AHashMap;
 use index_vec::{Idx, IndexVec};
-use std::fmt;
+use std::{cmp::Ordering, fmt};
 
 use crate::IndexedValue;
 
+/// A table mapping the indices of one domain to the indices of another domain of the same type.
+pub type IndexRemapping<T> = IndexVec<<T as IndexedValue>::Index, <T as IndexedValue>::Index>;
+
+/// An opaque token identifying a particular [`IndexedDomain`] in memory.
+///
+/// Two pointers to the same domain have the same id, so collections can cheaply check
+/// that they share a domain. The id is derived from the domain's address, so it changes
+/// if the domain is moved, and may be reused after the domain is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DomainId(usize);
+
 /// An indexed collection of objects.
 ///
 /// Contains a reverse-mapping from `T` to `T::Index` for efficient lookups of indices.
+#[derive(Clone)]
 pub struct IndexedDomain<T: IndexedValue> {
     domain: IndexVec<T::Index, T>,
     reverse_map: AHashMap<T, T::Index>,
@@ -29,6 +41,50 @@ impl<T: IndexedValue> IndexedDomain<T> {
         }
     }
 
+    /// Creates a new domain from an indexed vector, where duplicate values map to their first index.
+    ///
+    /// [`IndexedDomain::new`] maps duplicate values to their last index. In both cases, the
+    /// vector retains all of its entries, so [`IndexedDomain::value`] works for every index.
+    pub fn from_vec_first_wins(domain: IndexVec<T::Index, T>) -> Self {
+        let mut reverse_map = AHashMap::with_capacity(domain.len());
+        for (idx, value) in domain.iter_enumerated() {
+            reverse_map.entry(value.clone()).or_insert(idx);
+        }
+        IndexedDomain {
+            domain,
+            reverse_map,
+        }
+    }
+
+    /// Creates a new domain from an iterator, returning an error on the first duplicate element.
+    pub fn try_from_iter(iter: impl IntoIterator<Item = T>) -> Result<Self, DuplicateError<T>> {
+        let iter = iter.into_iter();
+        let mut domain = IndexedDomain::with_capacity(iter.size_hint().0);
+        for value in iter {
+            if let Some(index) = domain.try_index(&value) {
+                return Err(DuplicateError { value, index });
+            }
+            domain.insert(value);
+        }
+        Ok(domain)
+    }
+
+    /// Creates an empty domain with space for at least `capacity` elements.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexedDomain {
+            domain: IndexVec::with_capacity(capacity),
+            reverse_map: AHashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Reserves space for at least `additional` more elements.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.domain.reserve(additional);
+        self.reverse_map.reserve(additional);
+    }
+
     /// Gets the object corresponding to `index`.
     ///
     /// Panics if `index` is not within the domain.
@@ -45,6 +101,18 @@ impl<T: IndexedValue> IndexedDomain<T> {
         self.reverse_map[value]
     }
 
+    /// Gets the object corresponding to `index`, or `None` if `index` is not within the domain.
+    #[inline]
+    pub fn try_value(&self, index: T::Index) -> Option<&T> {
+        self.domain.get(index)
+    }
+
+    /// Gets the index corresponding to `value`, or `None` if `value` is not within the domain.
+    #[inline]
+    pub fn try_index(&self, value: &T) -> Option<T::Index> {
+        self.reverse_map.get(value).copied()
+    }
+
     /// Returns true if `value` is contained in the domain.
     #[inline]
     pub fn contains(&self, value: &T) -> bool {
@@ -54,7 +122,58 @@ impl<T: IndexedValue> IndexedDomain<T> {
     /// Adds `value` to the domain, returning its new index.
     #[inline]
     pub fn insert(&mut self, value: T) -> T::Index {
-        self.domain.push(value)
+        let idx = self.domain.push(value.clone());
+        self.reverse_map.insert(value, idx);
+        idx
+    }
+
+    /// Removes `value` from the domain, shifting every later element down by one index.
+    ///
+    /// Returns `None` if `value` is not in the domain. Otherwise, returns a table that maps each
+    /// old index to its new index, or `None` for the removed element. Removal invalidates every
+    /// index greater than or equal to the removed one, so any collections built on this domain
+    /// must be rebuilt with the table.
+    pub fn remove(&mut self, value: &T) -> Option<IndexVec<T::Index, Option<T::Index>>> {
+        let removed = self.reverse_map.remove(value)?;
+        self.domain.remove(removed);
+        for (idx, value) in self.domain.iter_enumerated().skip(removed.index()) {
+            *self.reverse_map.get_mut(value).unwrap() = idx;
+        }
+        let remap = (0..=self.domain.len())
+            .map(|i| match i.cmp(&removed.index()) {
+                Ordering::Less => Some(T::Index::from_usize(i)),
+                Ordering::Equal => None,
+                Ordering::Greater => Some(T::Index::from_usize(i - 1)),
+            })
+            .collect();
+        Some(remap)
+    }
+
+    /// Removes `value` from the domain by moving the last element into its place.
+    ///
+    /// Returns `None` if `value` is not in the domain. Otherwise, returns the removed index.
+    /// This is O(1), but unlike [`IndexedDomain::remove`] it does not preserve order: the element
+    /// previously at index `self.len()` (after removal) now has the returned index. All other
+    /// indices are unchanged.
+    pub fn remove_swap(&mut self, value: &T) -> Option<T::Index> {
+        let removed = self.reverse_map.remove(value)?;
+        self.domain.swap_remove(removed);
+        if let Some(moved) = self.domain.get(removed) {
+            *self.reverse_map.get_mut(moved).unwrap() = removed;
+        }
+        Some(removed)
+    }
+
+    /// Returns the index of `value`, adding it to the domain if it does not exist yet.
+    ///
+    /// The returned flag is true if `value` was newly inserted. Unlike [`IndexedDomain::ensure`],
+    /// this takes `value` by ownership, so it is never cloned when already present.
+    #[inline]
+    pub fn get_or_insert(&mut self, value: T) -> (T::Index, bool) {
+        match self.try_index(&value) {
+            Some(idx) => (idx, false),
+            None => (self.insert(value), true),
+        }
     }
 
     /// Returns immutable access to the underlying indexed vector.
@@ -79,13 +198,56 @@ impl<T: IndexedValue> IndexedDomain<T> {
     /// to the domain if it does not exist yet.
     #[inline]
     pub fn ensure(&mut self, value: &T) -> T::Index {
-        if !self.contains(value) {
-            self.insert(value.clone())
-        } else {
-            self.index(value)
+        match self.try_index(value) {
+            Some(idx) => idx,
+            None => self.insert(value.clone()),
         }
     }
 
+    /// Adds each element of `iter` to the domain if it does not exist yet, returning
+    /// the index of each element in iteration order.
+    ///
+    /// Like [`IndexedDomain::ensure`], but takes ownership of the elements.
+    pub fn extend_dedup(&mut self, iter: impl IntoIterator<Item = T>) -> Vec<T::Index> {
+        iter.into_iter()
+            .map(|value| match self.try_index(&value) {
+                Some(idx) => idx,
+                None => self.insert(value),
+            })
+            .collect()
+    }
+
+    /// Appends the elements of `other` to the end of `self`, in `other`'s order.
+    ///
+    /// Elements of `other` already in `self` are not duplicated. Indices of existing elements in
+    /// `self` are unchanged, and new elements are assigned consecutive indices after them. Returns
+    /// a table that maps each index in `other` to the corresponding index in `self`.
+    pub fn append(&mut self, other: &IndexedDomain<T>) -> IndexRemapping<T> {
+        other.iter().map(|value| self.ensure(value)).collect()
+    }
+
+    /// Returns a domain containing the elements of both `self` and `other`, along with tables
+    /// that map the indices of `self` and `other` respectively to indices in the combined domain.
+    ///
+    /// Elements of `self` keep their indices, and elements only in `other` follow them in
+    /// `other`'s order. Values present in both domains map to the same combined index.
+    pub fn union(
+        &self,
+        other: &IndexedDomain<T>,
+    ) -> (IndexedDomain<T>, IndexRemapping<T>, IndexRemapping<T>) {
+        let mut combined = self.clone();
+        let other_map = combined.append(other);
+        (combined, self.indices().collect(), other_map)
+    }
+
+    /// Returns a domain where the element at each index is `f` of the element at that index in `self`.
+    ///
+    /// Indices are preserved by position, so `B` may have a different index type than `T`.
+    /// `f` should be injective: like [`IndexedDomain::new`], duplicate outputs map to their last index.
+    pub fn map<B: IndexedValue>(&self, f: impl FnMut(&T) -> B) -> IndexedDomain<B> {
+        IndexedDomain::new(self.domain.iter().map(f).collect())
+    }
+
     /// Returns an iterator over all elements of the domain.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
@@ -105,8 +267,28 @@ impl<T: IndexedValue> IndexedDomain<T> {
     pub fn iter_enumerated(&self) -> impl Iterator<Item = (T::Index, &T)> + '_ {
         self.domain.iter_enumerated()
     }
+
+    /// Returns a token identifying this domain by its address.
+    ///
+    /// Use `==` instead to compare domains by their contents.
+    #[inline]
+    pub fn id(&self) -> DomainId {
+        DomainId(self as *const Self as usize)
+    }
+}
+
+impl<T: IndexedValue> PartialEq for IndexedDomain<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.domain == other.domain
+    }
 }
 
+impl<T: IndexedValue> Eq for IndexedDomain<T> {}
+
+/// Creates a new domain from an iterator.
+///
+/// Like [`IndexedDomain::new`], every element gets its own index, and duplicate elements map to
+/// their last index. Use [`IndexedDomain::try_from_iter`] to reject duplicates instead.
 impl<T: IndexedValue> FromIterator<T> for IndexedDomain<T> {
     fn from_iter<Iter: IntoIterator<Item = T>>(iter: Iter) -> Self {
         let domain = iter.into_iter().collect();
@@ -114,6 +296,37 @@ impl<T: IndexedValue> FromIterator<T> for IndexedDomain<T> {
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<T: IndexedValue + Send> rayon::iter::FromParallelIterator<T> for IndexedDomain<T> {
+    fn from_par_iter<Iter: rayon::iter::IntoParallelIterator<Item = T>>(iter: Iter) -> Self {
+        use rayon::iter::ParallelIterator;
+        let domain = iter.into_par_iter().collect::<Vec<_>>();
+        IndexedDomain::new(IndexVec::from_vec(domain))
+    }
+}
+
+/// Error for constructing a domain with a duplicate element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateError<T: IndexedValue> {
+    /// The duplicated element.
+    pub value: T,
+    /// The index of the first occurrence of the element.
+    pub index: T::Index,
+}
+
+impl<T: IndexedValue + fmt::Debug> fmt::Display for DuplicateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "duplicate element {:?}, first at index {}",
+            self.value,
+            self.index.index()
+        )
+    }
+}
+
+impl<T: IndexedValue + fmt::Debug> std::error::Error for DuplicateError<T> {}
+
 impl<T: IndexedValue + fmt::Debug> fmt::Debug for IndexedDomain<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self.domain)
@@ -134,4 +347,280 @@ fn test_domain() {
     assert!(d.contains(&mk("a")));
     assert!(!d.contains(&mk("c")));
     assert_eq!(d.len(), 2);
+
+    let mut d = d;
+    let c = d.insert(mk("c"));
+    assert_eq!(d.index(&mk("c")), c);
+    assert_eq!(d.ensure(&mk("c")), c);
+    assert_eq!(d.len(), 3);
+}
+
+#[test]
+fn test_domain_try() {
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    let d = IndexedDomain::from_iter([mk("a"), mk("b")]);
+    let b = d.index(&mk("b"));
+    assert_eq!(d.try_index(&mk("b")), Some(b));
+    assert_eq!(d.try_index(&mk("c")), None);
+    assert_eq!(d.try_value(b).map(String::as_str), Some("b"));
+    assert_eq!(d.try_value(Idx::from_usize(2)), None);
+}
+
+#[test]
+fn test_domain_append() {
+    use crate::{test_utils::TestIndexSet, IndexicalIteratorExt};
+    use std::rc::Rc;
+
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    let mut d1 = IndexedDomain::from_iter([mk("a"), mk("b")]);
+    let d2 = Rc::new(IndexedDomain::from_iter([mk("c"), mk("a"), mk("d")]));
+    let remap = d1.append(&d2);
+    assert_eq!(
+        d1.iter().map(String::as_str).collect::<Vec<_>>(),
+        vec!["a", "b", "c", "d"]
+    );
+    assert_eq!(
+        remap.iter().map(|idx| idx.index()).collect::<Vec<_>>(),
+        vec![2, 0, 3]
+    );
+
+    let d1 = Rc::new(d1);
+    let s2 = [mk("a"), mk("d")]
+        .into_iter()
+        .collect_indexical::<TestIndexSet<_>>(&d2);
+    let s1 = s2
+        .indices()
+        .map(|idx| remap[idx])
+        .collect_indexical::<TestIndexSet<_>>(&d1);
+    assert_eq!(s1.iter().collect::<Vec<_>>(), vec!["a", "d"]);
+}
+
+#[test]
+fn test_domain_remove() {
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    fn check_consistent(d: &IndexedDomain<String>) {
+        for (idx, value) in d.iter_enumerated() {
+            assert_eq!(d.index(value), idx);
+        }
+        assert_eq!(d.reverse_map.len(), d.len());
+    }
+
+    let mut d = IndexedDomain::from_iter([mk("a"), mk("b"), mk("c"), mk("d")]);
+    assert!(d.remove(&mk("e")).is_none());
+    let remap = d.remove(&mk("b")).unwrap();
+    assert_eq!(
+        remap
+            .iter()
+            .map(|idx| idx.map(|idx| idx.index()))
+            .collect::<Vec<_>>(),
+        vec![Some(0), None, Some(1), Some(2)]
+    );
+    assert_eq!(
+        d.iter().map(String::as_str).collect::<Vec<_>>(),
+        vec!["a", "c", "d"]
+    );
+    assert!(!d.contains(&mk("b")));
+    check_consistent(&d);
+
+    let a = d.remove_swap(&mk("a")).unwrap();
+    assert_eq!(a.index(), 0);
+    assert_eq!(
+        d.iter().map(String::as_str).collect::<Vec<_>>(),
+        vec!["d", "c"]
+    );
+    check_consistent(&d);
+
+    assert_eq!(d.remove_swap(&mk("c")).unwrap().index(), 1);
+    assert!(d.remove_swap(&mk("c")).is_none());
+    assert_eq!(d.iter().map(String::as_str).collect::<Vec<_>>(), vec!["d"]);
+    check_consistent(&d);
+}
+
+#[test]
+fn test_domain_get_or_insert() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static CLONES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[derive(PartialEq, Eq, Hash)]
+    struct Counted(u32);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.with(|c| c.set(c.get() + 1));
+            Counted(self.0)
+        }
+    }
+
+    crate::define_index_type! {
+        struct CountedIdx for Counted = u32;
+    }
+
+    let mut d = IndexedDomain::from_iter([Counted(0)]);
+    CLONES.with(|c| c.set(0));
+
+    let (idx, inserted) = d.get_or_insert(Counted(0));
+    assert_eq!((idx.index(), inserted), (0, false));
+    assert_eq!(CLONES.with(Cell::get), 0);
+
+    let (idx, inserted) = d.get_or_insert(Counted(1));
+    assert_eq!((idx.index(), inserted), (1, true));
+    assert_eq!(d.len(), 2);
+    assert_eq!(d.index(&Counted(1)), idx);
+}
+
+#[test]
+fn test_domain_capacity() {
+    let mut d = IndexedDomain::<usize>::with_capacity(10);
+    assert!(d.is_empty());
+    for i in 0..10 {
+        d.insert(i);
+    }
+    d.reserve(100);
+    for i in 10..110 {
+        d.insert(i);
+    }
+    assert_eq!(d.len(), 110);
+    assert_eq!(d.index(&50).index(), 50);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_domain_from_par_iter() {
+    use rayon::prelude::*;
+
+    let n = 10_000;
+    let values = (0..n).map(|i| (i * 7919) % 1000).collect::<Vec<usize>>();
+    let serial = values.iter().copied().collect::<IndexedDomain<_>>();
+    let parallel = values.par_iter().copied().collect::<IndexedDomain<_>>();
+    assert_eq!(serial.as_vec(), parallel.as_vec());
+    for value in serial.iter() {
+        assert_eq!(serial.index(value), parallel.index(value));
+    }
+}
+
+#[test]
+fn test_domain_first_wins() {
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    let vec = IndexVec::from_vec(vec![mk("a"), mk("b"), mk("a")]);
+    let last = IndexedDomain::new(vec.clone());
+    assert_eq!(last.index(&mk("a")).index(), 2);
+
+    let first = IndexedDomain::from_vec_first_wins(vec);
+    assert_eq!(first.index(&mk("a")).index(), 0);
+    assert_eq!(first.index(&mk("b")).index(), 1);
+    assert_eq!(first.len(), 3);
+    assert_eq!(first.value(Idx::from_usize(2)), "a");
+}
+
+#[test]
+fn test_domain_extend_dedup() {
+    let mut d = IndexedDomain::from_iter(["a", "b"].map(String::from));
+    let idxs = d.extend_dedup(["b", "c", "c", "d"].map(String::from));
+    let expected = [1, 2, 2, 3].map(crate::test_utils::StrIdx::from_usize);
+    assert_eq!(idxs, expected);
+    assert_eq!(d.len(), 4);
+    assert_eq!(d.value(idxs[3]), "d");
+}
+
+#[test]
+fn test_domain_try_from_iter() {
+    let d = IndexedDomain::try_from_iter(["a", "b"].map(String::from)).unwrap();
+    assert_eq!(d.len(), 2);
+
+    let err = IndexedDomain::try_from_iter(["a", "b", "a"].map(String::from)).unwrap_err();
+    assert_eq!(err.value, "a");
+    assert_eq!(err.index.index(), 0);
+
+    let d = IndexedDomain::from_iter(["a", "b", "a"].map(String::from));
+    assert_eq!(d.len(), 3);
+    assert_eq!(d.index(&String::from("a")).index(), 2);
+}
+
+#[test]
+fn test_domain_union() {
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    let d1 = IndexedDomain::from_iter([mk("a"), mk("b")]);
+    let d2 = IndexedDomain::from_iter([mk("b"), mk("c")]);
+    let (d, map1, map2) = d1.union(&d2);
+    assert_eq!(d.as_vec().raw, vec![mk("a"), mk("b"), mk("c")]);
+    for (idx, value) in d1.iter_enumerated() {
+        assert_eq!(d.value(map1[idx]), value);
+    }
+    for (idx, value) in d2.iter_enumerated() {
+        assert_eq!(d.value(map2[idx]), value);
+    }
+    assert_eq!(map1[d1.index(&mk("b"))], map2[d2.index(&mk("b"))]);
+}
+
+#[test]
+fn test_domain_map() {
+    let d = IndexedDomain::from_iter(["a", "bb", "ccc"].map(String::from));
+    let lens = d.map(String::len);
+    assert_eq!(lens.len(), d.len());
+    for (idx, value) in d.iter_enumerated() {
+        let len_idx = lens.index(&value.len());
+        assert_eq!(len_idx.index(), idx.index());
+    }
+}
+
+#[test]
+fn test_domain_generic_index() {
+    use crate::test_utils::{Labeled, LabeledIdx, Wrapper, WrapperIdx};
+
+    let d = IndexedDomain::from_iter([Wrapper(1u8), Wrapper(2)]);
+    assert_eq!(d.index(&Wrapper(2)), WrapperIdx::from_usize(1));
+
+    let key = String::from("a");
+    let d = IndexedDomain::from_iter([Labeled(&key, 0u32), Labeled(&key, 1)]);
+    assert_eq!(d.index(&Labeled(&key, 1)), LabeledIdx::from_usize(1));
+    assert_eq!(format!("{:?}", LabeledIdx::from_usize(1)), "L1");
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_domain_derive_indexed_value() {
+    index_vec::define_index_type! {
+        struct NodeId = u32;
+    }
+
+    #[derive(Clone, PartialEq, Eq, Hash, crate::IndexedValue)]
+    #[indexed_value(index = NodeId)]
+    struct Node(&'static str);
+
+    let d = IndexedDomain::from_iter([Node("a"), Node("b")]);
+    let idx: NodeId = d.index(&Node("b"));
+    assert_eq!(idx, NodeId::from_usize(1));
+    assert_eq!(d.value(idx).0, "b");
+}
+
+#[test]
+fn test_domain_eq_and_id() {
+    use std::rc::Rc;
+
+    let d1 = Rc::new(IndexedDomain::from_iter([1usize, 2, 3]));
+    let d2 = Rc::new(IndexedDomain::from_iter([1usize, 2, 3]));
+    let d3 = IndexedDomain::from_iter([3usize, 2, 1]);
+    assert_eq!(d1, d2);
+    assert_ne!(*d1, d3);
+
+    assert_eq!(d1.id(), Rc::clone(&d1).id());
+    assert_ne!(d1.id(), d2.id());
 }
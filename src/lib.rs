@@ -23,16 +23,28 @@ mod domain;
 pub mod map;
 mod matrix;
 pub mod pointer;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "rkyv")]
+pub mod rkyv;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod set;
 #[cfg(test)]
 mod test_utils;
+pub mod vec;
 
 #[doc(hidden)]
 pub use index_vec as _index_vec;
+#[cfg(feature = "derive")]
+pub use indexical_derive::IndexedValue;
 
-pub use domain::IndexedDomain;
-pub use matrix::IndexMatrix;
-pub use set::IndexSet;
+#[cfg(all(test, feature = "derive"))]
+extern crate self as indexical;
+
+pub use domain::{DomainId, DuplicateError, IndexRemapping, IndexedDomain};
+pub use matrix::{IndexMatrix, IndexMatrixDisplay};
+pub use set::{DomainMismatch, IndexSet, IndexSetIntoIter, SetBuilder};
 
 /// Coherence hack for the `ToIndex` trait.
 pub struct MarkerOwned;
@@ -60,7 +72,7 @@ impl<T: IndexedValue> ToIndex<T, MarkerOwned> for T {
     }
 }
 
-impl<'a, T: IndexedValue> ToIndex<T, MarkerRef> for &'a T {
+impl<T: IndexedValue> ToIndex<T, MarkerRef> for &T {
     #[inline]
     fn to_index(self, domain: &IndexedDomain<T>) -> T::Index {
         domain.index(self)
@@ -77,6 +89,21 @@ impl<T: IndexedValue> ToIndex<T, MarkerIndex> for T::Index {
 /// Links a type to its index.
 ///
 /// Should be automatically implemented by the [`define_index_type`] macro.
+///
+/// If you already have an index type (e.g. from [`index_vec::define_index_type`]), then
+/// with the `derive` feature you can instead derive the implementation:
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// index_vec::define_index_type! {
+///     pub struct NodeId = u32;
+/// }
+///
+/// #[derive(Clone, PartialEq, Eq, Hash, indexical::IndexedValue)]
+/// #[indexed_value(index = NodeId)]
+/// pub struct Node(String);
+/// # }
+/// ```
 pub trait IndexedValue: Clone + PartialEq + Eq + Hash {
     /// The index for `Self`.
     type Index: Idx;
@@ -86,24 +113,102 @@ pub trait IndexedValue: Clone + PartialEq + Eq + Hash {
 ///
 /// This is a thin wrapper around [`index_vec::define_index_type`]. The only
 /// modification is the `for $TYPE` syntax that generates the [`IndexedValue`]
-/// implementation.
+/// implementation. To associate an existing index type with an object type, use
+/// `#[derive(IndexedValue)]` instead (see [`IndexedValue`]).
+///
+/// The object type may have lifetime and type parameters. Every instantiation of the type
+/// shares the same index type. Bounds must be given in a `where` clause before the `=`:
+/// inline bounds like `for Foo<T: Clone>` are not supported.
+///
+/// ```
+/// # use std::hash::Hash;
+/// #[derive(Clone, PartialEq, Eq, Hash)]
+/// struct Pair<'a, K, V>(&'a K, V);
+///
+/// indexical::define_index_type! {
+///     pub struct PairIndex for Pair<'a, K, V> where K: Clone + Eq + Hash, V: Clone + Eq + Hash = u32;
+/// }
+///
+/// let domain = indexical::IndexedDomain::from_iter([Pair(&1, "a"), Pair(&2, "b")]);
+/// let idx: PairIndex = domain.index(&Pair(&2, "b"));
+/// assert_eq!(idx.index(), 1);
+/// ```
+///
+/// ```compile_fail
+/// # use std::hash::Hash;
+/// #[derive(Clone, PartialEq, Eq, Hash)]
+/// struct Wrapper<T>(T);
+///
+/// indexical::define_index_type! {
+///     pub struct WrapperIndex for Wrapper<T: Clone + Eq + Hash> = u32;
+/// }
+/// ```
 #[macro_export]
 macro_rules! define_index_type {
-  (
-    $(#[$attrs:meta])*
-    $v:vis struct $type:ident for $target:ident $(<$($l:lifetime),*>)? = $raw:ident;
+  (@where $attrs:tt $v:tt $type:tt $target:tt $generics:tt [$($w:tt)*]
+    = $raw:ident;
     $($CONFIG_NAME:ident = $value:expr;)* $(;)?
+  ) => {
+    $crate::define_index_type! {
+      @impl $attrs $v $type $target $generics [$($w)*] $raw [$($CONFIG_NAME = $value;)*]
+    }
+  };
+
+  (@where $attrs:tt $v:tt $type:tt $target:tt $generics:tt [$($w:tt)*] $next:tt $($rest:tt)*) => {
+    $crate::define_index_type! {
+      @where $attrs $v $type $target $generics [$($w)* $next] $($rest)*
+    }
+  };
+
+  (@impl [$($attrs:tt)*] [$v:vis] $type:ident $target:ident [$($g:tt),*] [$($w:tt)*] $raw:ident
+    [$($config:tt)*]
   ) => {
     $crate::_index_vec::define_index_type! {
-      $(#[$attrs])*
+      $($attrs)*
       $v struct $type = $raw;
-      $($CONFIG_NAME = $value;)*
+      $($config)*
     }
 
-    impl $(<$($l),*>)? $crate::IndexedValue for $target $(<$($l),*>)? {
+    impl<$($g),*> $crate::IndexedValue for $target<$($g),*> where $($w)* {
       type Index = $type;
     }
-  }
+  };
+
+  (
+    $(#[$attrs:meta])*
+    $v:vis struct $type:ident for $target:ident $(<$($g:tt),*>)? = $raw:ident;
+    $($CONFIG_NAME:ident = $value:expr;)* $(;)?
+  ) => {
+    $crate::define_index_type! {
+      @impl [$(#[$attrs])*] [$v] $type $target [$($($g),*)?] [] $raw [$($CONFIG_NAME = $value;)*]
+    }
+  };
+
+  (
+    $(#[$attrs:meta])*
+    $v:vis struct $type:ident for $target:ident $(<$($g:tt),*>)? where $($rest:tt)*
+  ) => {
+    $crate::define_index_type! {
+      @where [$(#[$attrs])*] [$v] $type $target [$($($g),*)?] [] $($rest)*
+    }
+  };
+}
+
+/// A join-semilattice, i.e. a type with a least upper bound operation.
+///
+/// This mirrors `rustc_mir_dataflow::JoinSemiLattice` for dataflow analyses outside the compiler.
+/// With the `rustc` feature, [`IndexSet`] and [`IndexMatrix`] also implement `JoinSemiLattice` via [`Join`].
+pub trait Join {
+    /// Updates `self` to the least upper bound of `self` and `other`, returning true if `self` changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+/// A meet-semilattice, i.e. a type with a greatest lower bound operation.
+///
+/// The dual of [`Join`].
+pub trait Meet {
+    /// Updates `self` to the greatest lower bound of `self` and `other`, returning true if `self` changed.
+    fn meet(&mut self, other: &Self) -> bool;
 }
 
 /// Workaround for GAT lifetime issue.
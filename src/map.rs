@@ -2,7 +2,10 @@
 
 use std::{
     collections::hash_map,
+    iter,
+    marker::PhantomData,
     ops::{Index, IndexMut},
+    vec,
 };
 
 use ahash::AHashMap;
@@ -19,7 +22,7 @@ use crate::{
 /// number of keys.
 pub struct SparseIndexMap<'a, K: IndexedValue + 'a, V, P: PointerFamily<'a>> {
     map: AHashMap<K::Index, V>,
-    domain: P::Pointer<IndexedDomain<K>>,
+    pub(crate) domain: P::Pointer<IndexedDomain<K>>,
 }
 
 /// [`SparseIndexMap`] specialized to the [`RcFamily`].
@@ -85,12 +88,75 @@ where
         self.map.insert(idx, value);
     }
 
+    /// Removes the value for a given key, returning it if it existed.
+    #[inline]
+    pub fn remove<M>(&mut self, key: impl ToIndex<K, M>) -> Option<V> {
+        let idx = key.to_index(&self.domain);
+        self.map.remove(&idx)
+    }
+
+    /// Returns true if the map contains a value for the given key.
+    #[inline]
+    pub fn contains_key<M>(&self, key: impl ToIndex<K, M>) -> bool {
+        let idx = key.to_index(&self.domain);
+        self.map.contains_key(&idx)
+    }
+
     /// Returns an iterator over the values of the map.
     #[inline]
     pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
         self.map.values()
     }
 
+    /// Returns a mutable iterator over the values of the map.
+    #[inline]
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.map.values_mut()
+    }
+
+    /// Returns a mutable iterator over the key/value pairs of the map.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K::Index, &mut V)> + '_ {
+        self.map.iter_mut()
+    }
+
+    /// Retains only the entries for which `f` returns true.
+    #[inline]
+    pub fn retain(&mut self, mut f: impl FnMut(K::Index, &mut V) -> bool) {
+        self.map.retain(|idx, value| f(*idx, value));
+    }
+
+    /// Copies every entry of `other` into `self`, overwriting the values of existing keys.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn overlay(&mut self, other: &Self)
+    where
+        V: Clone,
+    {
+        self.merge_with(other, |value, other_value| value.clone_from(other_value));
+    }
+
+    /// Copies every entry of `other` into `self`, calling `f` to combine the values of keys in both.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn merge_with(&mut self, other: &Self, mut f: impl FnMut(&mut V, &V))
+    where
+        V: Clone,
+    {
+        debug_assert!(
+            P::ptr_eq(&self.domain, &other.domain),
+            "maps do not share the same domain"
+        );
+        for (idx, other_value) in &other.map {
+            match self.map.entry(*idx) {
+                hash_map::Entry::Occupied(mut entry) => f(entry.get_mut(), other_value),
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(other_value.clone());
+                }
+            }
+        }
+    }
+
     /// Returns a mutable entry into the map for the given key.
     #[inline]
     pub fn entry<M>(&mut self, key: impl ToIndex<K, M>) -> hash_map::Entry<'_, K::Index, V> {
@@ -167,13 +233,26 @@ where
     }
 }
 
+impl<'a, K, V, P> IntoIterator for SparseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    type Item = (K::Index, V);
+    type IntoIter = hash_map::IntoIter<K::Index, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
 /// A mapping from indexed keys to values, implemented densely with a vector.
 ///
 /// This is more time-efficient than the [`SparseIndexMap`] for lookup,
 /// but it consumes more memory for missing elements.
 pub struct DenseIndexMap<'a, K: IndexedValue + 'a, V, P: PointerFamily<'a>> {
-    map: IndexVec<K::Index, V>,
-    domain: P::Pointer<IndexedDomain<K>>,
+    pub(crate) map: IndexVec<K::Index, Option<V>>,
+    pub(crate) domain: P::Pointer<IndexedDomain<K>>,
 }
 
 /// [`DenseIndexMap`] specialized to the [`RcFamily`].
@@ -190,14 +269,26 @@ where
     K: IndexedValue + 'a,
     P: PointerFamily<'a>,
 {
-    /// Constructs a new map with an initial element of `mk_elem(i)` for each `i` in `domain`.
+    /// Constructs an empty map within the given domain.
+    ///
+    /// Before 0.7, `new` took a function `mk_elem` to initialize every key. Use
+    /// [`DenseIndexMap::from_fn`] with `|i| Some(mk_elem(i))` instead.
     #[inline]
-    pub fn new(domain: &P::Pointer<IndexedDomain<K>>, mk_elem: impl FnMut(K::Index) -> V) -> Self {
-        Self::from_vec(domain, IndexVec::from_iter(domain.indices().map(mk_elem)))
+    pub fn new(domain: &P::Pointer<IndexedDomain<K>>) -> Self {
+        Self::from_fn(domain, |_| None)
     }
 
+    /// Constructs a new map where each key `i` in `domain` maps to `f(i)`, if it is `Some`.
     #[inline]
-    fn from_vec(domain: &P::Pointer<IndexedDomain<K>>, map: IndexVec<K::Index, V>) -> Self {
+    pub fn from_fn(
+        domain: &P::Pointer<IndexedDomain<K>>,
+        f: impl FnMut(K::Index) -> Option<V>,
+    ) -> Self {
+        Self::from_vec(domain, IndexVec::from_iter(domain.indices().map(f)))
+    }
+
+    #[inline]
+    fn from_vec(domain: &P::Pointer<IndexedDomain<K>>, map: IndexVec<K::Index, Option<V>>) -> Self {
         DenseIndexMap {
             map,
             domain: domain.clone(),
@@ -208,14 +299,14 @@ where
     #[inline]
     pub fn get<M>(&self, idx: impl ToIndex<K, M>) -> Option<&V> {
         let idx = idx.to_index(&self.domain);
-        self.map.get(idx)
+        self.map.get(idx).and_then(Option::as_ref)
     }
 
     /// Returns a mutable reference to a value for a given key if it exists.
     #[inline]
     pub fn get_mut<M>(&mut self, idx: impl ToIndex<K, M>) -> Option<&mut V> {
         let idx = idx.to_index(&self.domain);
-        self.map.get_mut(idx)
+        self.map.get_mut(idx).and_then(Option::as_mut)
     }
 
     /// Returns a reference to a value for a given key.
@@ -225,7 +316,11 @@ where
     #[inline]
     pub unsafe fn get_unchecked<M>(&self, idx: impl ToIndex<K, M>) -> &V {
         let idx = idx.to_index(&self.domain);
-        self.map.raw.get_unchecked(idx.index())
+        self.map
+            .raw
+            .get_unchecked(idx.index())
+            .as_ref()
+            .unwrap_unchecked()
     }
 
     /// Returns a mutable reference to a value for a given key.
@@ -235,20 +330,164 @@ where
     #[inline]
     pub unsafe fn get_unchecked_mut<M>(&mut self, idx: impl ToIndex<K, M>) -> &mut V {
         let idx = idx.to_index(&self.domain);
-        self.map.raw.get_unchecked_mut(idx.index())
+        self.map
+            .raw
+            .get_unchecked_mut(idx.index())
+            .as_mut()
+            .unwrap_unchecked()
     }
 
     /// Inserts the key/value pair into `self`.
     #[inline]
     pub fn insert<M>(&mut self, idx: impl ToIndex<K, M>, value: V) {
         let idx = idx.to_index(&self.domain);
-        self.map[idx] = value;
+        self.map[idx] = Some(value);
+    }
+
+    /// Removes the value for a given key, returning it if it existed.
+    #[inline]
+    pub fn remove<M>(&mut self, idx: impl ToIndex<K, M>) -> Option<V> {
+        let idx = idx.to_index(&self.domain);
+        self.map[idx].take()
+    }
+
+    /// Returns true if the map contains a value for the given key.
+    #[inline]
+    pub fn contains_key<M>(&self, idx: impl ToIndex<K, M>) -> bool {
+        self.get(idx).is_some()
+    }
+
+    /// Returns a mutable reference to the value for a given key, inserting `f()` if it does not exist.
+    #[inline]
+    pub fn get_or_insert_with<M>(
+        &mut self,
+        idx: impl ToIndex<K, M>,
+        f: impl FnOnce() -> V,
+    ) -> &mut V {
+        let idx = idx.to_index(&self.domain);
+        self.map[idx].get_or_insert_with(f)
     }
 
     /// Returns an iterator over the values of the map.
     #[inline]
     pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
-        self.map.iter()
+        self.map.iter().filter_map(Option::as_ref)
+    }
+
+    /// Returns the number of entries in the map, which may be less than the size of the domain.
+    ///
+    /// The count is not cached, so this scans every slot in the domain. Caching it would add
+    /// bookkeeping to every insertion and removal, which are the common case for this map.
+    pub fn len(&self) -> usize {
+        self.map.iter().filter(|value| value.is_some()).count()
+    }
+
+    /// Returns true if the map has no entries.
+    ///
+    /// Like [`DenseIndexMap::len`], this scans the domain, but it stops at the first entry.
+    pub fn is_empty(&self) -> bool {
+        self.map.iter().all(Option::is_none)
+    }
+
+    /// Returns an iterator over the keys of the map, in index order.
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = K::Index> + '_ {
+        self.iter().map(|(idx, _)| idx)
+    }
+
+    /// Returns an iterator over the key/value pairs of the map, in index order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (K::Index, &V)> + '_ {
+        self.map
+            .iter_enumerated()
+            .filter_map(|(idx, value)| Some((idx, value.as_ref()?)))
+    }
+
+    /// Returns a mutable iterator over the key/value pairs of the map, in index order.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (K::Index, &mut V)> + '_ {
+        self.map
+            .iter_mut_enumerated()
+            .filter_map(|(idx, value)| Some((idx, value.as_mut()?)))
+    }
+
+    /// Copies every entry of `other` into `self`, overwriting the values of existing keys.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn overlay(&mut self, other: &Self)
+    where
+        V: Clone,
+    {
+        self.merge_with(other, |value, other_value| value.clone_from(other_value));
+    }
+
+    /// Copies every entry of `other` into `self`, calling `f` to combine the values of keys in both.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn merge_with(&mut self, other: &Self, mut f: impl FnMut(&mut V, &V))
+    where
+        V: Clone,
+    {
+        debug_assert!(
+            P::ptr_eq(&self.domain, &other.domain),
+            "maps do not share the same domain"
+        );
+        for (slot, other_value) in self.map.iter_mut().zip(&other.map) {
+            if let Some(other_value) = other_value {
+                match slot {
+                    Some(value) => f(value, other_value),
+                    None => *slot = Some(other_value.clone()),
+                }
+            }
+        }
+    }
+
+    /// Retains only the entries for which `f` returns true, removing the rest.
+    pub fn retain(&mut self, mut f: impl FnMut(K::Index, &mut V) -> bool) {
+        for (idx, slot) in self.map.iter_mut_enumerated() {
+            if let Some(value) = slot {
+                if !f(idx, value) {
+                    *slot = None;
+                }
+            }
+        }
+    }
+}
+
+/// Owning iterator over the entries of a [`DenseIndexMap`], in index order.
+pub struct DenseIndexMapIntoIter<K: IndexedValue, V> {
+    iter: iter::Enumerate<vec::IntoIter<Option<V>>>,
+    _marker: PhantomData<K>,
+}
+
+impl<K: IndexedValue, V> Iterator for DenseIndexMapIntoIter<K, V> {
+    type Item = (K::Index, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .find_map(|(i, value)| Some((K::Index::from_usize(i), value?)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<'a, K, V, P> IntoIterator for DenseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    type Item = (K::Index, V);
+    type IntoIter = DenseIndexMapIntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        DenseIndexMapIntoIter {
+            iter: self.map.raw.into_iter().enumerate(),
+            _marker: PhantomData,
+        }
     }
 }
 
@@ -286,13 +525,207 @@ where
         iter: impl Iterator<Item = (U, V)>,
         domain: &P::Pointer<IndexedDomain<K>>,
     ) -> Self {
-        let mut map = iter
-            .map(|(u, v)| (u.to_index(domain), v))
-            .collect::<AHashMap<_, _>>();
-        let vec = domain
-            .indices()
-            .map(|i| map.remove(&i).unwrap_or_else(|| panic!("Cannot use FromIndexicalIterator for a DenseIndexMap with a sparse key set")))
-            .collect::<IndexVec<_, _>>();
-        DenseIndexMap::from_vec(domain, vec)
+        let mut map = DenseIndexMap::new(domain);
+        for (u, v) in iter {
+            map.insert(u, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DenseRcIndexMap, SparseRcIndexMap};
+    use crate::IndexedDomain;
+    use std::rc::Rc;
+
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    #[test]
+    fn test_dense_get_or_insert_with() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let mut map = DenseRcIndexMap::new(&d);
+        assert_eq!(map.get(mk("a")), None);
+
+        let mut calls = 0;
+        for _ in 0..2 {
+            *map.get_or_insert_with(mk("a"), || {
+                calls += 1;
+                0
+            }) += 1;
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(map.get(mk("a")), Some(&2));
+        assert_eq!(map.get(mk("b")), None);
+    }
+
+    #[test]
+    fn test_dense_iter() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut map = DenseRcIndexMap::new(&d);
+        map.insert(mk("c"), 3);
+        map.insert(mk("a"), 1);
+
+        let (a, c) = (d.index(&mk("a")), d.index(&mk("c")));
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(a, &1), (c, &3)]);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![a, c]);
+
+        for (_, value) in map.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(map.values().collect::<Vec<_>>(), vec![&10, &30]);
+    }
+
+    #[test]
+    fn test_remove_contains_key() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+
+        let mut sparse = SparseRcIndexMap::new(&d);
+        sparse.insert(mk("a"), 1);
+        assert!(sparse.contains_key(mk("a")));
+        assert!(!sparse.contains_key(mk("b")));
+        assert_eq!(sparse.remove(mk("b")), None);
+        assert_eq!(sparse.remove(mk("a")), Some(1));
+        assert_eq!(sparse.remove(mk("a")), None);
+        assert!(!sparse.contains_key(mk("a")));
+
+        let mut dense = DenseRcIndexMap::new(&d);
+        dense.insert(mk("a"), 1);
+        assert!(dense.contains_key(mk("a")));
+        assert!(!dense.contains_key(mk("b")));
+        assert_eq!(dense.remove(mk("b")), None);
+        assert_eq!(dense.remove(mk("a")), Some(1));
+        assert_eq!(dense.remove(mk("a")), None);
+        assert!(!dense.contains_key(mk("a")));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let (a, c) = (d.index(&mk("a")), d.index(&mk("c")));
+
+        let mut sparse = SparseRcIndexMap::new(&d);
+        sparse.insert(a, mk("x"));
+        sparse.insert(c, mk("z"));
+        let mut entries = sparse.into_iter().collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, vec![(a, mk("x")), (c, mk("z"))]);
+
+        let mut dense = DenseRcIndexMap::new(&d);
+        dense.insert(c, mk("z"));
+        dense.insert(a, mk("x"));
+        let entries = dense.into_iter().collect::<Vec<_>>();
+        assert_eq!(entries, vec![(a, mk("x")), (c, mk("z"))]);
+    }
+
+    #[test]
+    fn test_dense_from_fn() {
+        let d = Rc::new(IndexedDomain::from_iter(0..5usize));
+        let map = DenseRcIndexMap::from_fn(&d, |idx| {
+            let i = *d.value(idx);
+            (i % 2 == 0).then_some(i * 10)
+        });
+        assert_eq!(map.values().collect::<Vec<_>>(), vec![&0, &20, &40]);
+        assert!(!map.contains_key(1));
+    }
+
+    #[test]
+    fn test_sparse_iter_mut() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut map = SparseRcIndexMap::new(&d);
+        map.insert(mk("a"), 1);
+        map.insert(mk("c"), 3);
+
+        for value in map.values_mut() {
+            *value *= 2;
+        }
+        assert_eq!(map.get(mk("a")), Some(&2));
+        assert_eq!(map.get(mk("c")), Some(&6));
+
+        for (idx, value) in map.iter_mut() {
+            *value += idx.index();
+        }
+        assert_eq!(map.get(mk("a")), Some(&2));
+        assert_eq!(map.get(mk("c")), Some(&8));
+    }
+
+    #[test]
+    fn test_retain() {
+        let d = Rc::new(IndexedDomain::from_iter(0..6usize));
+
+        let mut sparse = SparseRcIndexMap::new(&d);
+        let mut dense = DenseRcIndexMap::new(&d);
+        for i in [0, 2, 3, 5] {
+            sparse.insert(i, i * 10);
+            dense.insert(i, i * 10);
+        }
+
+        sparse.retain(|_, value| *value > 20);
+        let mut entries = sparse.into_iter().collect::<Vec<_>>();
+        entries.sort();
+        let expected = vec![(d.index(&3), 30), (d.index(&5), 50)];
+        assert_eq!(entries, expected);
+
+        dense.retain(|_, value| *value > 20);
+        assert_eq!(dense.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_dense_len() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut map = DenseRcIndexMap::new(&d);
+        assert!(map.is_empty());
+        map.insert(mk("a"), 1);
+        map.insert(mk("c"), 3);
+        assert_eq!(map.len(), 2);
+        assert!(!map.is_empty());
+    }
+
+    #[test]
+    fn test_overlay_merge_with() {
+        let d = Rc::new(IndexedDomain::from_iter(0..4usize));
+        let sparse = |entries: &[(usize, usize)]| {
+            let mut map = SparseRcIndexMap::new(&d);
+            for (k, v) in entries {
+                map.insert(k, *v);
+            }
+            map
+        };
+        let sorted = |map: SparseRcIndexMap<usize, usize>| {
+            let mut entries = map
+                .into_iter()
+                .map(|(k, v)| (k.index(), v))
+                .collect::<Vec<_>>();
+            entries.sort();
+            entries
+        };
+        let (a, b, c) = (&[(0, 1), (1, 2)], &[(1, 20), (2, 30)], &[(3, 40)]);
+
+        let mut overlaid = sparse(a);
+        overlaid.overlay(&sparse(b));
+        assert_eq!(sorted(overlaid), vec![(0, 1), (1, 20), (2, 30)]);
+
+        let mut merged = sparse(a);
+        merged.merge_with(&sparse(b), |x, y| *x += y);
+        assert_eq!(sorted(merged), vec![(0, 1), (1, 22), (2, 30)]);
+
+        let mut disjoint = sparse(a);
+        disjoint.merge_with(&sparse(c), |_, _| unreachable!());
+        assert_eq!(sorted(disjoint), vec![(0, 1), (1, 2), (3, 40)]);
+
+        let dense = |entries: &[(usize, usize)]| {
+            let map = sparse(entries);
+            DenseRcIndexMap::from_fn(&d, |idx| map.get(idx).copied())
+        };
+        let mut da = dense(a);
+        da.merge_with(&dense(c), |_, _| unreachable!());
+        assert_eq!(da.len(), 3);
+        da.merge_with(&dense(b), |x, y| *x += y);
+        let entries = da.iter().map(|(k, v)| (k.index(), *v)).collect::<Vec<_>>();
+        assert_eq!(entries, vec![(0, 1), (1, 22), (2, 30), (3, 40)]);
+        da.overlay(&dense(b));
+        assert_eq!(da.get(1), Some(&20));
     }
 }
@@ -1,10 +1,10 @@
 use ahash::AHashMap;
 use splitmut::SplitMut;
-use std::{fmt, hash::Hash};
+use std::{cmp::Ordering, collections::hash_map::Entry, fmt, hash::Hash};
 
 use crate::{
-    bitset::BitSet, pointer::PointerFamily, Captures, IndexSet, IndexedDomain, IndexedValue,
-    ToIndex,
+    bitset::BitSet, pointer::PointerFamily, Captures, FromIndexicalIterator, IndexSet,
+    IndexedDomain, IndexedValue, Join, ToIndex,
 };
 
 /// An unordered collections of pairs `(R, C)`, implemented with a sparse bit-matrix.
@@ -33,6 +33,26 @@ where
         }
     }
 
+    /// Creates a matrix from an iterator of rows, unioning the sets of duplicate rows.
+    ///
+    /// Each set must be within `col_domain`.
+    pub fn from_rows(
+        col_domain: &P::Pointer<IndexedDomain<C>>,
+        rows: impl IntoIterator<Item = (R, IndexSet<'a, C, S, P>)>,
+    ) -> Self {
+        let mut mtx = IndexMatrix::new(col_domain);
+        for (row, set) in rows {
+            match mtx.matrix.entry(row) {
+                Entry::Occupied(mut entry) => entry.get_mut().union(&set),
+                Entry::Vacant(entry) => {
+                    mtx.empty_set.debug_assert_same_domain(&set);
+                    entry.insert(set);
+                }
+            }
+        }
+        mtx
+    }
+
     pub(crate) fn ensure_row(&mut self, row: R) -> &mut IndexSet<'a, C, S, P> {
         self.matrix
             .entry(row)
@@ -45,6 +65,20 @@ where
         self.ensure_row(row).insert(col)
     }
 
+    /// Removes a pair `(row, col)` from the matrix, returning true if it was present.
+    ///
+    /// If `row` becomes empty, it is removed entirely, so it no longer appears in [`IndexMatrix::rows`].
+    pub fn remove<M>(&mut self, row: &R, col: impl ToIndex<C, M>) -> bool {
+        let Some(set) = self.matrix.get_mut(row) else {
+            return false;
+        };
+        let removed = set.remove(col);
+        if set.is_empty() {
+            self.matrix.remove(row);
+        }
+        removed
+    }
+
     /// Adds all elements of `from` into the row `into`.
     pub fn union_into_row(&mut self, into: R, from: &IndexSet<'a, C, S, P>) -> bool {
         self.ensure_row(into).union_changed(from)
@@ -64,16 +98,146 @@ where
         to.union_changed(from)
     }
 
-    /// Returns an iterator over the elements in `row`.
+    /// Returns mutable references to several distinct rows at once.
+    ///
+    /// Returns `None` if any row is missing from the matrix or requested more than once.
+    pub fn get_disjoint_rows_mut<const N: usize>(
+        &mut self,
+        rows: [&R; N],
+    ) -> Option<[&mut IndexSet<'a, C, S, P>; N]> {
+        let mut muts = self.matrix.get_muts();
+        let sets = rows
+            .into_iter()
+            .map(|row| muts.at(row).ok())
+            .collect::<Option<Vec<_>>>()?;
+        sets.try_into().ok()
+    }
+
+    /// Removes all elements of the row `into` not in `from`, returning true if `self` changed.
+    pub fn intersect_into_row(&mut self, into: R, from: &IndexSet<'a, C, S, P>) -> bool {
+        match self.matrix.get_mut(&into) {
+            Some(set) => set.intersect_changed(from),
+            None => false,
+        }
+    }
+
+    /// Removes all elements of `from` from the row `into`, returning true if `self` changed.
+    pub fn subtract_into_row(&mut self, into: R, from: &IndexSet<'a, C, S, P>) -> bool {
+        match self.matrix.get_mut(&into) {
+            Some(set) => set.subtract_changed(from),
+            None => false,
+        }
+    }
+
+    /// Removes all elements of the row `to` not in the row `from`, returning true if `self` changed.
+    pub fn intersect_rows(&mut self, from: R, to: R) -> bool {
+        if from == to || !self.matrix.contains_key(&to) {
+            return false;
+        }
+
+        if !self.matrix.contains_key(&from) {
+            // A missing row is empty, so the intersection is empty.
+            let changed = !self.matrix[&to].is_empty();
+            self.clear_row(&to);
+            return changed;
+        }
+
+        // SAFETY: `from` != `to` therefore this is a disjoint mutable borrow
+        let (from, to) = unsafe { self.matrix.get2_unchecked_mut(&from, &to) };
+        to.intersect_changed(from)
+    }
+
+    /// Removes all elements of the row `from` from the row `to`, returning true if `self` changed.
+    pub fn subtract_rows(&mut self, from: R, to: R) -> bool {
+        if !self.matrix.contains_key(&from) || !self.matrix.contains_key(&to) {
+            return false;
+        }
+
+        if from == to {
+            let changed = !self.matrix[&to].is_empty();
+            self.clear_row(&to);
+            return changed;
+        }
+
+        // SAFETY: `from` != `to` therefore this is a disjoint mutable borrow
+        let (from, to) = unsafe { self.matrix.get2_unchecked_mut(&from, &to) };
+        to.subtract_changed(from)
+    }
+
+    /// Merges `other` into `self` row-by-row, returning true if `self` changed.
+    ///
+    /// For each row in both matrices, `combine` is called with `self`'s row and `other`'s row.
+    /// Rows only in `other` are cloned into `self` if `clone_missing` is true, and ignored otherwise.
+    pub fn merge_with(
+        &mut self,
+        other: &Self,
+        clone_missing: bool,
+        mut combine: impl FnMut(&mut IndexSet<'a, C, S, P>, &IndexSet<'a, C, S, P>),
+    ) -> bool {
+        let mut changed = false;
+        for (row, col) in other.matrix.iter() {
+            match self.matrix.get_mut(row) {
+                Some(set) => {
+                    let orig = set.clone();
+                    combine(set, col);
+                    changed |= *set != orig;
+                }
+                None if clone_missing => {
+                    changed |= !col.is_empty();
+                    self.matrix.insert(row.clone(), col.clone());
+                }
+                None => {}
+            }
+        }
+        changed
+    }
+
+    /// Returns an iterator over the elements in `row`, in ascending index order.
     pub fn row(&self, row: &R) -> impl Iterator<Item = &C> + Captures<'a> + '_ {
         self.matrix.get(row).into_iter().flat_map(|set| set.iter())
     }
 
+    /// Returns the elements in `row` sorted by `cmp`.
+    ///
+    /// Useful for a deterministic display order that doesn't depend on the column indices.
+    pub fn row_sorted_by(&self, row: &R, cmp: impl FnMut(&&C, &&C) -> Ordering) -> Vec<&C> {
+        let mut cols = self.row(row).collect::<Vec<_>>();
+        cols.sort_by(cmp);
+        cols
+    }
+
     /// Returns an iterator over all rows in the matrix.
     pub fn rows(&self) -> impl Iterator<Item = (&R, &IndexSet<'a, C, S, P>)> + Captures<'a> + '_ {
         self.matrix.iter()
     }
 
+    /// Returns the number of rows stored in the matrix.
+    ///
+    /// This includes rows that are present but empty, e.g. after every column
+    /// of a row has been intersected away.
+    pub fn num_rows(&self) -> usize {
+        self.matrix.len()
+    }
+
+    /// Returns the number of pairs `(row, col)` in the matrix.
+    pub fn len(&self) -> usize {
+        self.matrix.values().map(IndexSet::len).sum()
+    }
+
+    /// Returns true if the matrix contains no pairs, even if it has empty rows.
+    pub fn is_empty(&self) -> bool {
+        self.matrix.values().all(IndexSet::is_empty)
+    }
+
+    /// Returns an iterator over all pairs `(row, col)` in the matrix.
+    ///
+    /// Rows are in an unspecified order, and columns within a row are in ascending index order.
+    pub fn pairs(&self) -> impl Iterator<Item = (&R, &C)> + Captures<'a> + '_ {
+        self.matrix
+            .iter()
+            .flat_map(|(row, cols)| cols.iter().map(move |col| (row, col)))
+    }
+
     /// Returns the [`IndexSet`] for a particular `row`.
     pub fn row_set(&self, row: &R) -> &IndexSet<'a, C, S, P> {
         self.matrix.get(row).unwrap_or(&self.empty_set)
@@ -84,10 +248,163 @@ where
         self.matrix.remove(row);
     }
 
+    /// Removes every row `row` where `f(row, set)` returns false.
+    pub fn retain_rows(&mut self, mut f: impl FnMut(&R, &IndexSet<'a, C, S, P>) -> bool) {
+        self.matrix.retain(|row, set| f(row, set));
+    }
+
+    /// Removes every pair `(row, col)` where `f(row, col)` returns false.
+    ///
+    /// Rows left empty afterward are removed, including rows that were already empty.
+    pub fn retain_pairs(&mut self, mut f: impl FnMut(&R, &C) -> bool) {
+        self.matrix.retain(|row, set| {
+            set.retain(|col| f(row, col));
+            !set.is_empty()
+        });
+    }
+
+    /// Shrinks the memory used by the row map and by each row as much as possible.
+    ///
+    /// Useful after clearing many rows.
+    pub fn shrink_to_fit(&mut self) {
+        self.matrix.shrink_to_fit();
+        for set in self.matrix.values_mut() {
+            set.shrink_to_fit();
+        }
+    }
+
     /// Returns the [`IndexedDomain`] for the column type.
     pub fn col_domain(&self) -> &P::Pointer<IndexedDomain<C>> {
         &self.col_domain
     }
+
+    /// Returns a readable [`Display`](fmt::Display) of `self` for logging.
+    ///
+    /// Each row is rendered on its own line as `r -> {c1, c2, ...}`, with columns in
+    /// ascending index order. Rows are sorted by their rendered text so the output is deterministic.
+    pub fn display(&self) -> IndexMatrixDisplay<'_, 'a, R, C, S, P>
+    where
+        R: fmt::Display,
+        C: fmt::Display,
+    {
+        IndexMatrixDisplay { matrix: self }
+    }
+}
+
+/// Helper for displaying an [`IndexMatrix`], returned by [`IndexMatrix::display`].
+pub struct IndexMatrixDisplay<'m, 'a, R, C: IndexedValue + 'a, S: BitSet, P: PointerFamily<'a>> {
+    matrix: &'m IndexMatrix<'a, R, C, S, P>,
+}
+
+impl<'m, 'a, R, C, S, P> fmt::Display for IndexMatrixDisplay<'m, 'a, R, C, S, P>
+where
+    R: PartialEq + Eq + Hash + Clone + fmt::Display,
+    C: IndexedValue + fmt::Display + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = self
+            .matrix
+            .rows()
+            .map(|(row, cols)| {
+                let cols = cols.iter().map(ToString::to_string).collect::<Vec<_>>();
+                format!("{row} -> {{{}}}", cols.join(", "))
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl<'a, R, C, S, P> IndexMatrix<'a, R, C, S, P>
+where
+    R: IndexedValue + 'a,
+    C: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    /// Returns the inverse relation, containing `(c, r)` iff `(r, c)` is in `self`.
+    ///
+    /// `row_domain` must contain every row of `self`, and becomes the column domain of the result.
+    pub fn transpose(
+        &self,
+        row_domain: &P::Pointer<IndexedDomain<R>>,
+    ) -> IndexMatrix<'a, C, R, S, P> {
+        let mut transposed = IndexMatrix::new(row_domain);
+        for (row, cols) in self.rows() {
+            let row = row_domain.index(row);
+            for col in cols.iter() {
+                transposed.insert(col.clone(), row);
+            }
+        }
+        transposed
+    }
+}
+
+impl<'a, T, S, P> IndexMatrix<'a, T, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    /// Returns the transitive closure of `self`, containing `(a, c)` if there is a path
+    /// of pairs `(a, b)`, ..., `(b', c)` in `self`.
+    ///
+    /// Each row is repeatedly unioned with the rows of its successors until a fixpoint is reached.
+    /// This takes O(V·E) row unions for V rows and E pairs, so it is intended for modest domains.
+    pub fn transitive_closure(&self) -> Self {
+        let mut closure = self.clone();
+        let rows = closure.matrix.keys().cloned().collect::<Vec<_>>();
+        loop {
+            let mut changed = false;
+            for row in &rows {
+                let succs = closure.row(row).cloned().collect::<Vec<_>>();
+                for succ in succs {
+                    if closure.matrix.contains_key(&succ) {
+                        changed |= closure.union_rows(succ, row.clone());
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        closure
+    }
+}
+
+#[cfg(feature = "petgraph")]
+impl<'a, T, S, P> IndexMatrix<'a, T, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    /// Converts `self` into a [`petgraph::Graph`] with an edge `r -> c` for each pair `(r, c)`.
+    ///
+    /// The graph has a node for every element of the domain, and the
+    /// [`NodeIndex`](petgraph::graph::NodeIndex) of each node is the index of its element.
+    /// Rows whose key is not in the domain have no node, so their edges are skipped.
+    pub fn to_petgraph(&self) -> petgraph::Graph<T, ()> {
+        use index_vec::Idx;
+        use petgraph::graph::NodeIndex;
+        let domain = &self.col_domain;
+        let mut graph = petgraph::Graph::with_capacity(domain.len(), 0);
+        for value in domain.iter() {
+            graph.add_node(value.clone());
+        }
+        for (row, cols) in self.rows() {
+            let Some(src) = domain.try_index(row) else {
+                continue;
+            };
+            let src = NodeIndex::new(src.index());
+            for dst in cols.indices() {
+                graph.add_edge(src, NodeIndex::new(dst.index()), ());
+            }
+        }
+        graph
+    }
 }
 
 impl<'a, R, C, S, P> PartialEq for IndexMatrix<'a, R, C, S, P>
@@ -140,6 +457,43 @@ where
     }
 }
 
+impl<'a, R, C, S, P, M, U> FromIndexicalIterator<'a, C, P, M, (R, U)>
+    for IndexMatrix<'a, R, C, S, P>
+where
+    R: PartialEq + Eq + Hash + Clone,
+    C: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+    U: ToIndex<C, M>,
+{
+    fn from_indexical_iter(
+        iter: impl Iterator<Item = (R, U)>,
+        domain: &P::Pointer<IndexedDomain<C>>,
+    ) -> Self {
+        let mut mtx = IndexMatrix::new(domain);
+        for (row, col) in iter {
+            mtx.insert(row, col);
+        }
+        mtx
+    }
+}
+
+impl<'a, R, C, S, P> Join for IndexMatrix<'a, R, C, S, P>
+where
+    R: PartialEq + Eq + Hash + Clone,
+    C: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn join(&mut self, other: &Self) -> bool {
+        let mut changed = false;
+        for (row, col) in other.matrix.iter() {
+            changed |= self.ensure_row(row.clone()).union_changed(col);
+        }
+        changed
+    }
+}
+
 impl<'a, R, C, S, P> fmt::Debug for IndexMatrix<'a, R, C, S, P>
 where
     R: PartialEq + Eq + Hash + Clone + fmt::Debug,
@@ -154,7 +508,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{test_utils::TestIndexMatrix, IndexedDomain};
+    use crate::{
+        test_utils::{TestIndexMatrix, TestIndexSet},
+        IndexedDomain, IndexicalIteratorExt, Join,
+    };
     use std::rc::Rc;
 
     fn mk(s: &str) -> String {
@@ -173,4 +530,322 @@ mod test {
         assert!(mtx.union_rows(0, 1));
         assert_eq!(mtx.row(&1).collect::<Vec<_>>(), vec!["b", "c"]);
     }
+
+    #[test]
+    fn test_indexmatrix_merge_with() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("a"));
+        mtx.insert(0, mk("b"));
+        mtx.insert(1, mk("c"));
+
+        let mut other = TestIndexMatrix::new(&col_domain);
+        other.insert(0, mk("b"));
+        other.insert(0, mk("c"));
+        other.insert(2, mk("a"));
+
+        let mut merged = mtx.clone();
+        assert!(merged.merge_with(&other, false, |set, other| set.intersect(other)));
+        assert_eq!(merged.row(&0).collect::<Vec<_>>(), vec!["b"]);
+        assert_eq!(merged.row(&1).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(merged.row(&2).count(), 0);
+        assert!(!merged.merge_with(&other, false, |set, other| set.intersect(other)));
+
+        let mut merged = mtx.clone();
+        assert!(merged.merge_with(&other, true, |set, other| set.intersect(other)));
+        assert_eq!(merged.row(&0).collect::<Vec<_>>(), vec!["b"]);
+        assert_eq!(merged.row(&2).collect::<Vec<_>>(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_indexmatrix_collect_indexical() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let pairs: Vec<(u32, String)> = vec![(0, mk("a")), (1, mk("c")), (0, mk("b"))];
+        let mtx: TestIndexMatrix<_, _> = pairs.into_iter().collect_indexical(&col_domain);
+        assert_eq!(mtx.row(&0).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(mtx.row(&1).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(mtx.num_rows(), 2);
+    }
+
+    #[test]
+    fn test_indexmatrix_len() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        assert_eq!((mtx.num_rows(), mtx.len(), mtx.is_empty()), (0, 0, true));
+
+        mtx.insert(0, mk("a"));
+        mtx.insert(0, mk("b"));
+        mtx.insert(1, mk("c"));
+        assert_eq!((mtx.num_rows(), mtx.len(), mtx.is_empty()), (2, 3, false));
+
+        mtx.clear_row(&0);
+        assert_eq!((mtx.num_rows(), mtx.len(), mtx.is_empty()), (1, 1, false));
+
+        // Empty-but-present rows count towards `num_rows` but not `len` or `is_empty`.
+        mtx.ensure_row(2);
+        mtx.intersect_into_row(1, &TestIndexSet::new(&col_domain));
+        assert_eq!((mtx.num_rows(), mtx.len(), mtx.is_empty()), (2, 0, true));
+    }
+
+    #[test]
+    fn test_indexmatrix_retain() {
+        let domain = Rc::new(IndexedDomain::from_iter(0..4usize));
+        let mut mtx = TestIndexMatrix::new(&domain);
+        for (row, col) in [(0, 0), (0, 1), (1, 1), (2, 3), (3, 3)] {
+            mtx.insert(row, col);
+        }
+
+        let mut rows = mtx.clone();
+        rows.retain_rows(|row, _| row % 2 == 0);
+        let mut pairs = rows.pairs().map(|(r, c)| (*r, *c)).collect::<Vec<_>>();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 0), (0, 1), (2, 3)]);
+
+        mtx.retain_pairs(|row, col| row == col);
+        let mut pairs = mtx.pairs().map(|(r, c)| (*r, *c)).collect::<Vec<_>>();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 0), (1, 1), (3, 3)]);
+        assert_eq!(mtx.num_rows(), 3);
+    }
+
+    #[test]
+    fn test_indexmatrix_display() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(1, mk("c"));
+        mtx.insert(0, mk("c"));
+        mtx.insert(0, mk("a"));
+        assert_eq!(mtx.display().to_string(), "0 -> {a, c}\n1 -> {c}");
+        assert_eq!(
+            TestIndexMatrix::<i32, _>::new(&col_domain)
+                .display()
+                .to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_indexmatrix_row_sorted_by() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("c"), mk("a"), mk("b")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("b"));
+        mtx.insert(0, mk("c"));
+        mtx.insert(0, mk("a"));
+        assert_eq!(mtx.row(&0).collect::<Vec<_>>(), vec!["c", "a", "b"]);
+        assert_eq!(mtx.row_sorted_by(&0, |a, b| a.cmp(b)), vec!["a", "b", "c"]);
+        assert!(mtx.row_sorted_by(&1, |a, b| a.cmp(b)).is_empty());
+    }
+
+    #[test]
+    fn test_indexmatrix_from_rows() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mk_set = |elems: &[&str]| {
+            elems
+                .iter()
+                .map(|s| mk(s))
+                .collect_indexical::<TestIndexSet<_>>(&col_domain)
+        };
+        let mtx = TestIndexMatrix::from_rows(
+            &col_domain,
+            [
+                (0, mk_set(&["a"])),
+                (1, mk_set(&["c"])),
+                (0, mk_set(&["b"])),
+            ],
+        );
+        assert_eq!(mtx.row(&0).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(mtx.row(&1).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(mtx.rows().count(), 2);
+    }
+
+    #[test]
+    fn test_indexmatrix_shrink_to_fit() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        for i in 0..100 {
+            mtx.insert(i, mk("a"));
+        }
+        for i in 1..100 {
+            mtx.clear_row(&i);
+        }
+        let capacity = mtx.matrix.capacity();
+        mtx.shrink_to_fit();
+        assert!(mtx.matrix.capacity() < capacity);
+        assert_eq!(mtx.row(&0).collect::<Vec<_>>(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_indexmatrix_transpose() {
+        let row_domain = Rc::new(IndexedDomain::from_iter(0..3usize));
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("a"));
+        mtx.insert(0, mk("b"));
+        mtx.insert(2, mk("b"));
+
+        let transposed = mtx.transpose(&row_domain);
+        assert_eq!(transposed.row(&mk("a")).collect::<Vec<_>>(), vec![&0]);
+        assert_eq!(transposed.row(&mk("b")).collect::<Vec<_>>(), vec![&0, &2]);
+        assert_eq!(transposed.row(&mk("c")).count(), 0);
+
+        assert_eq!(transposed.transpose(&col_domain), mtx);
+    }
+
+    #[test]
+    fn test_indexmatrix_intersect_subtract() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("a"));
+        mtx.insert(0, mk("b"));
+        mtx.insert(1, mk("b"));
+        mtx.insert(1, mk("c"));
+
+        let mut m = mtx.clone();
+        assert!(m.intersect_rows(0, 1));
+        assert_eq!(m.row(&1).collect::<Vec<_>>(), vec!["b"]);
+        assert!(!m.intersect_rows(0, 1));
+        assert!(m.intersect_rows(2, 1));
+        assert_eq!(m.row(&1).count(), 0);
+        assert!(!m.intersect_rows(2, 1));
+
+        let mut m = mtx.clone();
+        assert!(m.subtract_rows(0, 1));
+        assert_eq!(m.row(&1).collect::<Vec<_>>(), vec!["c"]);
+        assert!(!m.subtract_rows(0, 1));
+        assert!(!m.subtract_rows(2, 1));
+        assert!(m.subtract_rows(1, 1));
+        assert_eq!(m.row(&1).count(), 0);
+
+        let set = [mk("b"), mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&col_domain);
+        let mut m = mtx.clone();
+        assert!(m.intersect_into_row(0, &set));
+        assert_eq!(m.row(&0).collect::<Vec<_>>(), vec!["b"]);
+        assert!(m.subtract_into_row(1, &set));
+        assert_eq!(m.row(&1).count(), 0);
+        assert!(!m.intersect_into_row(2, &set));
+        assert!(!m.subtract_into_row(2, &set));
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn test_indexmatrix_to_petgraph() {
+        let domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&domain);
+        mtx.insert(mk("a"), mk("b"));
+        mtx.insert(mk("a"), mk("c"));
+        mtx.insert(mk("b"), mk("c"));
+
+        let graph = mtx.to_petgraph();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        let a = petgraph::graph::NodeIndex::new(0);
+        assert_eq!(graph[a], "a");
+        assert_eq!(graph.neighbors(a).count(), 2);
+
+        mtx.insert(mk("z"), mk("a"));
+        let graph = mtx.to_petgraph();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_indexmatrix_remove() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("a"));
+        mtx.insert(0, mk("b"));
+
+        assert!(mtx.remove(&0, mk("a")));
+        assert!(!mtx.remove(&0, mk("a")));
+        assert!(!mtx.remove(&0, mk("c")));
+        assert!(!mtx.remove(&1, mk("a")));
+        assert_eq!(mtx.row(&0).collect::<Vec<_>>(), vec!["b"]);
+
+        assert!(mtx.remove(&0, mk("b")));
+        assert_eq!(mtx.rows().count(), 0);
+    }
+
+    #[test]
+    fn test_indexmatrix_pairs() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(1, mk("c"));
+        mtx.insert(0, mk("b"));
+        mtx.insert(1, mk("a"));
+
+        let mut pairs = mtx.pairs().collect::<Vec<_>>();
+        pairs.sort();
+        assert_eq!(pairs, vec![(&0, &mk("b")), (&1, &mk("a")), (&1, &mk("c"))]);
+    }
+
+    #[test]
+    fn test_indexmatrix_transitive_closure() {
+        let domain = Rc::new(IndexedDomain::from_iter(0..4usize));
+
+        let mut chain = TestIndexMatrix::new(&domain);
+        chain.insert(0, 1);
+        chain.insert(1, 2);
+        chain.insert(2, 3);
+        let closure = chain.transitive_closure();
+        assert_eq!(closure.row(&0).collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(closure.row(&1).collect::<Vec<_>>(), vec![&2, &3]);
+        assert_eq!(closure.row(&2).collect::<Vec<_>>(), vec![&3]);
+        assert_eq!(closure.row(&3).count(), 0);
+
+        let mut cycle = TestIndexMatrix::new(&domain);
+        cycle.insert(0, 1);
+        cycle.insert(1, 2);
+        cycle.insert(2, 0);
+        let closure = cycle.transitive_closure();
+        for i in 0..3 {
+            assert_eq!(closure.row(&i).collect::<Vec<_>>(), vec![&0, &1, &2]);
+        }
+        assert_eq!(closure.transitive_closure(), closure);
+    }
+
+    #[test]
+    fn test_indexmatrix_get_disjoint_rows_mut() {
+        let col_domain = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut mtx = TestIndexMatrix::new(&col_domain);
+        mtx.insert(0, mk("a"));
+        mtx.insert(1, mk("b"));
+        mtx.insert(2, mk("c"));
+
+        let [r0, r2] = mtx.get_disjoint_rows_mut([&0, &2]).unwrap();
+        r2.union(r0);
+        r0.clear();
+        assert_eq!(mtx.row(&0).count(), 0);
+        assert_eq!(mtx.row(&2).collect::<Vec<_>>(), vec!["a", "c"]);
+
+        assert!(mtx.get_disjoint_rows_mut([&0, &0]).is_none());
+        assert!(mtx.get_disjoint_rows_mut([&1, &2, &3]).is_none());
+    }
+
+    #[test]
+    fn test_indexmatrix_join_fixpoint() {
+        // Transitive closure of 0 -> 1 -> 2 -> 3, joining each row with its successors' rows.
+        let d = Rc::new(IndexedDomain::from_iter(0..4usize));
+        let mut mtx: TestIndexMatrix<usize, usize> =
+            [(0, 1), (1, 2), (2, 3)].into_iter().collect_indexical(&d);
+        loop {
+            let mut next = mtx.clone();
+            for (row, cols) in mtx.rows() {
+                for col in cols.iter() {
+                    next.union_into_row(*row, mtx.row_set(col));
+                }
+            }
+            if !mtx.join(&next) {
+                break;
+            }
+        }
+
+        assert_eq!(
+            mtx.row_set(&0).iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(mtx.row_set(&2).iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert!(mtx.row_set(&3).is_empty());
+    }
 }
@@ -2,8 +2,9 @@
 
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::rc::Rc;
-use std::sync::Arc;
+use std::ptr;
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
 
 /// Abstraction over smart pointers.
 ///
@@ -12,6 +13,26 @@ use std::sync::Arc;
 pub trait PointerFamily<'a> {
     /// Pointer type for a given family.
     type Pointer<T: 'a>: Deref<Target = T> + Clone;
+
+    /// Returns true if `a` and `b` point to the same object.
+    fn ptr_eq<T: 'a>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool {
+        ptr::eq::<T>(&**a, &**b)
+    }
+}
+
+/// Pointer families with non-owning weak references.
+///
+/// A weak reference to a domain can be used to break reference cycles, e.g. when
+/// the domain indirectly owns collections that point back to it.
+pub trait WeakPointerFamily<'a>: PointerFamily<'a> {
+    /// Weak pointer type for a given family.
+    type Weak<T: 'a>: Clone;
+
+    /// Creates a weak pointer to the same allocation as `ptr`.
+    fn downgrade<T: 'a>(ptr: &Self::Pointer<T>) -> Self::Weak<T>;
+
+    /// Recovers a strong pointer from `weak`, or returns `None` if the allocation was dropped.
+    fn upgrade<T: 'a>(weak: &Self::Weak<T>) -> Option<Self::Pointer<T>>;
 }
 
 /// Family of [`Arc`] pointers.
@@ -19,6 +40,22 @@ pub struct ArcFamily;
 
 impl<'a> PointerFamily<'a> for ArcFamily {
     type Pointer<T: 'a> = Arc<T>;
+
+    fn ptr_eq<T: 'a>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+}
+
+impl<'a> WeakPointerFamily<'a> for ArcFamily {
+    type Weak<T: 'a> = sync::Weak<T>;
+
+    fn downgrade<T: 'a>(ptr: &Self::Pointer<T>) -> Self::Weak<T> {
+        Arc::downgrade(ptr)
+    }
+
+    fn upgrade<T: 'a>(weak: &Self::Weak<T>) -> Option<Self::Pointer<T>> {
+        weak.upgrade()
+    }
 }
 
 /// Family of [`Rc`] pointers.
@@ -26,11 +63,184 @@ pub struct RcFamily;
 
 impl<'a> PointerFamily<'a> for RcFamily {
     type Pointer<T: 'a> = Rc<T>;
+
+    fn ptr_eq<T: 'a>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+}
+
+impl<'a> WeakPointerFamily<'a> for RcFamily {
+    type Weak<T: 'a> = rc::Weak<T>;
+
+    fn downgrade<T: 'a>(ptr: &Self::Pointer<T>) -> Self::Weak<T> {
+        Rc::downgrade(ptr)
+    }
+
+    fn upgrade<T: 'a>(weak: &Self::Weak<T>) -> Option<Self::Pointer<T>> {
+        weak.upgrade()
+    }
 }
 
 /// Family of `&`-references.
+///
+/// This is the family to use for a domain with a single owner, such as a `Box`. A `BoxFamily`
+/// is not possible because every collection holds its own copy of the domain pointer, which
+/// requires [`PointerFamily::Pointer`] to be `Clone` without cloning the domain itself. Instead,
+/// keep the owned domain alive for the duration of the analysis and borrow it:
+///
+/// ```
+/// # use indexical::{IndexedDomain, bitset::bitvec::RefIndexSet};
+/// # indexical::define_index_type! { pub struct StringIndex for MyString = u32; }
+/// # #[derive(PartialEq, Eq, Clone, Hash)]
+/// # pub struct MyString(String);
+/// let domain = Box::new(IndexedDomain::from_iter([
+///     MyString(String::from("Hello")), MyString(String::from("world"))
+/// ]));
+/// let domain: &IndexedDomain<_> = &domain;
+/// let mut set = RefIndexSet::new(&domain);
+/// set.insert(MyString(String::from("world")));
+/// assert!(set.contains(MyString(String::from("world"))));
+/// assert!(!set.contains(MyString(String::from("Hello"))));
+/// ```
 pub struct RefFamily<'a>(PhantomData<&'a ()>);
 
 impl<'a> PointerFamily<'a> for RefFamily<'a> {
     type Pointer<T: 'a> = &'a T;
+
+    fn ptr_eq<T: 'a>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool {
+        ptr::eq(*a, *b)
+    }
+}
+
+/// Family of copy-on-write pointers.
+///
+/// A [`CowPtr`] starts out borrowing a domain like [`RefFamily`], and is promoted to an owned
+/// [`Rc`] the first time the domain is mutated through [`CowPtr::to_mut`]. This avoids an up-front
+/// heap allocation for domains that are usually, but not always, left unchanged.
+///
+/// (`std::borrow::Cow` itself cannot be used here, since it requires `T: ToOwned` for every `T`.)
+pub struct CowFamily<'a>(PhantomData<&'a ()>);
+
+impl<'a> PointerFamily<'a> for CowFamily<'a> {
+    type Pointer<T: 'a> = CowPtr<'a, T>;
+}
+
+/// A copy-on-write pointer, either borrowed or reference-counted.
+///
+/// Cloning a `CowPtr` never clones the underlying value, so cloning a collection
+/// (e.g. [`IndexSet`](crate::IndexSet) or [`IndexMatrix`](crate::IndexMatrix)) over a
+/// [`CowFamily`] domain is as cheap as with the [`RefFamily`] or [`RcFamily`]. Mutating the
+/// domain through one `CowPtr` does not affect the other pointers: existing collections keep
+/// the original domain, so collections must be built from the mutated pointer to use new elements.
+pub enum CowPtr<'a, T> {
+    /// A borrowed value.
+    Borrowed(&'a T),
+    /// An owned, shared value.
+    Owned(Rc<T>),
+}
+
+impl<'a, T> CowPtr<'a, T> {
+    /// Returns a mutable reference to the value, cloning it if it is borrowed or shared.
+    pub fn to_mut(&mut self) -> &mut T
+    where
+        T: Clone,
+    {
+        if let CowPtr::Borrowed(value) = self {
+            *self = CowPtr::Owned(Rc::new((*value).clone()));
+        }
+        match self {
+            CowPtr::Owned(value) => Rc::make_mut(value),
+            CowPtr::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// Returns true if the value is owned.
+    pub fn is_owned(&self) -> bool {
+        matches!(self, CowPtr::Owned(_))
+    }
+}
+
+impl<'a, T> Deref for CowPtr<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            CowPtr::Borrowed(value) => value,
+            CowPtr::Owned(value) => value,
+        }
+    }
+}
+
+impl<'a, T> Clone for CowPtr<'a, T> {
+    fn clone(&self) -> Self {
+        match self {
+            CowPtr::Borrowed(value) => CowPtr::Borrowed(value),
+            CowPtr::Owned(value) => CowPtr::Owned(Rc::clone(value)),
+        }
+    }
+}
+
+impl<'a, T> From<&'a T> for CowPtr<'a, T> {
+    fn from(value: &'a T) -> Self {
+        CowPtr::Borrowed(value)
+    }
+}
+
+impl<'a, T> From<Rc<T>> for CowPtr<'a, T> {
+    fn from(value: Rc<T>) -> Self {
+        CowPtr::Owned(value)
+    }
+}
+
+#[test]
+fn test_cow_family() {
+    use crate::{bitset::bitvec::BitVec, IndexSet, IndexedDomain};
+
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    let base = IndexedDomain::from_iter([mk("a"), mk("b")]);
+    let mut domain = CowPtr::from(&base);
+    let mut set = IndexSet::<_, BitVec, CowFamily>::new(&domain);
+    set.insert(mk("a"));
+    assert!(!domain.is_owned());
+
+    let c = domain.to_mut().insert(mk("c"));
+    assert!(domain.is_owned());
+    assert_eq!(base.len(), 2);
+    assert_eq!(domain.len(), 3);
+    let mut set_clone = set.clone();
+    set_clone.insert_all();
+    assert_eq!(set_clone.len(), 2);
+
+    let mut set2 = IndexSet::<_, BitVec, CowFamily>::new(&domain);
+    set2.insert(c);
+    assert!(set2.contains(mk("c")));
+}
+
+#[test]
+fn test_ptr_eq() {
+    use crate::IndexedDomain;
+
+    fn mk_domain() -> IndexedDomain<String> {
+        IndexedDomain::from_iter([String::from("a")])
+    }
+
+    let (d1, d2) = (Rc::new(mk_domain()), Rc::new(mk_domain()));
+    assert!(RcFamily::ptr_eq(&d1, &d1.clone()));
+    assert!(!RcFamily::ptr_eq(&d1, &d2));
+
+    let (d1, d2) = (Arc::new(mk_domain()), Arc::new(mk_domain()));
+    assert!(ArcFamily::ptr_eq(&d1, &d1.clone()));
+    assert!(!ArcFamily::ptr_eq(&d1, &d2));
+
+    let (d1, d2) = (mk_domain(), mk_domain());
+    let (r1, r2) = (&d1, &d2);
+    assert!(RefFamily::ptr_eq(&r1, &Clone::clone(&r1)));
+    assert!(!RefFamily::ptr_eq(&r1, &r2));
+
+    let (c1, c2) = (CowPtr::from(&d1), CowPtr::from(&d2));
+    assert!(CowFamily::ptr_eq(&c1, &c1.clone()));
+    assert!(!CowFamily::ptr_eq(&c1, &c2));
 }
@@ -0,0 +1,122 @@
+//! Strategies for generating indexical types with [`proptest`](mod@::proptest).
+//!
+//! ```
+//! # use indexical::{proptest::{arb_domain, arb_index_set}, bitset::bitvec::IndexSet};
+//! # use proptest::prelude::*;
+//! # use std::rc::Rc;
+//! #[derive(PartialEq, Eq, Clone, Hash, Debug)]
+//! pub struct Name(String);
+//! indexical::define_index_type! {
+//!     pub struct NameIndex for Name = u32;
+//! }
+//!
+//! proptest! {
+//!     # #![proptest_config(ProptestConfig::with_cases(8))]
+//!     fn set_within_domain(
+//!         set in arb_domain("[a-z]+".prop_map(Name), 0..10)
+//!             .prop_flat_map(|domain| arb_index_set(Rc::new(domain)))
+//!     ) {
+//!         let set: IndexSet<Name> = set;
+//!         prop_assert!(set.len() < 10);
+//!     }
+//! }
+//! # set_within_domain();
+//! ```
+
+use std::{collections::HashSet, fmt, ops::Range, rc::Rc};
+
+use ::proptest::{collection, prelude::*};
+
+use crate::{
+    bitset::BitSet, pointer::RcFamily, vec::RcIndexVec, IndexSet, IndexedDomain, IndexedValue,
+};
+
+/// Generates a domain of distinct elements drawn from `elems`.
+///
+/// The number of draws is sampled from `size`, and duplicate draws are dropped (keeping
+/// the first), so the domain may be smaller than `size.start` if `elems` has few values.
+pub fn arb_domain<T>(
+    elems: impl Strategy<Value = T>,
+    size: Range<usize>,
+) -> impl Strategy<Value = IndexedDomain<T>>
+where
+    T: IndexedValue + fmt::Debug,
+{
+    collection::vec(elems, size).prop_map(|elems| {
+        let mut seen = HashSet::new();
+        elems
+            .into_iter()
+            .filter(|elem| seen.insert(elem.clone()))
+            .collect()
+    })
+}
+
+/// Generates a set containing an arbitrary subset of `domain`.
+pub fn arb_index_set<T, S>(
+    domain: Rc<IndexedDomain<T>>,
+) -> impl Strategy<Value = IndexSet<'static, T, S, RcFamily>>
+where
+    T: IndexedValue + fmt::Debug + 'static,
+    S: BitSet,
+{
+    collection::vec(any::<bool>(), domain.len()).prop_map(move |bits| {
+        let mut set = IndexSet::new(&domain);
+        for (idx, _) in domain.indices().zip(bits).filter(|(_, bit)| *bit) {
+            set.insert(idx);
+        }
+        set
+    })
+}
+
+/// Generates a vector over `domain` whose values are drawn from `values`.
+pub fn arb_index_vec<T, V>(
+    domain: Rc<IndexedDomain<T>>,
+    values: impl Strategy<Value = V>,
+) -> impl Strategy<Value = RcIndexVec<'static, T, V>>
+where
+    T: IndexedValue + 'static,
+    V: fmt::Debug,
+{
+    collection::vec(values, domain.len()).prop_map(move |values| {
+        let mut values = values.into_iter();
+        RcIndexVec::from_fn(|_| values.next().unwrap(), &domain)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{arb_domain, arb_index_set, arb_index_vec};
+    use crate::{test_utils::TestIndexSet, IndexicalIteratorExt};
+    use ::proptest::prelude::*;
+    use std::rc::Rc;
+
+    proptest! {
+        #[test]
+        fn test_arb_domain(domain in arb_domain("[a-z]{1,3}", 0..20)) {
+            prop_assert!(domain.len() < 20);
+            for (idx, value) in domain.iter_enumerated() {
+                prop_assert_eq!(domain.index(value), idx);
+            }
+        }
+
+        #[test]
+        fn test_arb_index_set_collect_indexical(
+            (domain, set) in arb_domain("[a-z]{1,3}", 0..20).prop_flat_map(|domain| {
+                let domain = Rc::new(domain);
+                (Just(Rc::clone(&domain)), arb_index_set(domain))
+            })
+        ) {
+            let set: TestIndexSet<String> = set;
+            let set2: TestIndexSet<String> = set.iter().cloned().collect_indexical(&domain);
+            prop_assert_eq!(set, set2);
+        }
+
+        #[test]
+        fn test_arb_index_vec(
+            vec in arb_domain("[a-z]{1,3}", 0..20)
+                .prop_flat_map(|domain| arb_index_vec(Rc::new(domain), 0..10u32))
+        ) {
+            prop_assert!(vec.iter().all(|value| *value < 10));
+        }
+    }
+}
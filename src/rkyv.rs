@@ -0,0 +1,181 @@
+//! Zero-copy archiving support via [`rkyv`].
+//!
+//! An [`IndexedDomain`] archives as the vector of its elements, as an [`ArchivedIndexedDomain`].
+//! The archived domain supports looking up values by index without deserializing, e.g. from a
+//! memory-mapped file. Deserializing (or calling [`IndexedDomain::from_archived`]) rebuilds
+//! the reverse map, so index lookups by value are only available on the deserialized domain.
+//! Deserializing fails if the archive contains a duplicate element.
+//!
+//! ```
+//! # use indexical::{IndexedDomain, rkyv::ArchivedIndexedDomain};
+//! # use rkyv::rancor::Error;
+//! #[derive(PartialEq, Eq, Clone, Hash, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+//! pub struct MyString(String);
+//! indexical::define_index_type! {
+//!     pub struct StringIndex for MyString = u32;
+//! }
+//!
+//! let domain = IndexedDomain::from_iter([
+//!     MyString(String::from("Hello")), MyString(String::from("world"))
+//! ]);
+//! let bytes = rkyv::to_bytes::<Error>(&domain).unwrap();
+//!
+//! let archived = rkyv::access::<ArchivedIndexedDomain<MyString>, Error>(&bytes).unwrap();
+//! assert_eq!(archived.value(StringIndex::from_usize(1)).0, "world");
+//!
+//! let domain2 = IndexedDomain::from_archived::<Error>(archived).unwrap();
+//! assert_eq!(domain2.index(&MyString(String::from("world"))), StringIndex::from_usize(1));
+//! ```
+
+use ::rkyv::{
+    bytecheck::CheckBytes,
+    munge::munge,
+    rancor::{Fallible, Source},
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Portable, Serialize,
+};
+use index_vec::Idx;
+use std::fmt;
+
+use crate::{IndexedDomain, IndexedValue};
+
+/// An archived [`IndexedDomain`].
+#[derive(Portable, CheckBytes)]
+#[rkyv(crate = ::rkyv)]
+#[bytecheck(crate = ::rkyv::bytecheck)]
+#[repr(transparent)]
+pub struct ArchivedIndexedDomain<T: IndexedValue + Archive> {
+    domain: ArchivedVec<T::Archived>,
+}
+
+impl<T: IndexedValue + Archive> ArchivedIndexedDomain<T> {
+    /// Gets the archived value corresponding to an index.
+    ///
+    /// Panics if the index is not within the domain.
+    #[inline]
+    pub fn value(&self, index: T::Index) -> &T::Archived {
+        &self.domain[index.index()]
+    }
+
+    /// Gets the archived value corresponding to an index, if it is within the domain.
+    #[inline]
+    pub fn try_value(&self, index: T::Index) -> Option<&T::Archived> {
+        self.domain.get(index.index())
+    }
+
+    /// Returns the archived values in index order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T::Archived] {
+        self.domain.as_slice()
+    }
+
+    /// Returns the number of elements in the domain.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.domain.len()
+    }
+
+    /// Returns true if the domain is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.domain.is_empty()
+    }
+}
+
+impl<T: IndexedValue + Archive> Archive for IndexedDomain<T> {
+    type Archived = ArchivedIndexedDomain<T>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedIndexedDomain { domain } = out);
+        ArchivedVec::resolve_from_slice(self.as_vec().as_raw_slice(), resolver, domain);
+    }
+}
+
+impl<T, S> Serialize<S> for IndexedDomain<T>
+where
+    T: IndexedValue + Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::serialize_from_slice(self.as_vec().as_raw_slice(), serializer)
+    }
+}
+
+impl<T, D> Deserialize<IndexedDomain<T>, D> for ArchivedIndexedDomain<T>
+where
+    T: IndexedValue + Archive,
+    ArchivedVec<T::Archived>: Deserialize<Vec<T>, D>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<IndexedDomain<T>, D::Error> {
+        let domain: Vec<T> = self.domain.deserialize(deserializer)?;
+        IndexedDomain::try_from_iter(domain).map_err(|err| {
+            D::Error::new(DuplicateArchivedElement {
+                index: err.index.index(),
+            })
+        })
+    }
+}
+
+/// Error for deserializing an archived domain that contains a duplicate element.
+#[derive(Debug)]
+struct DuplicateArchivedElement {
+    index: usize,
+}
+
+impl fmt::Display for DuplicateArchivedElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "archived domain contains a duplicate of the element at index {}",
+            self.index
+        )
+    }
+}
+
+impl std::error::Error for DuplicateArchivedElement {}
+
+impl<T: IndexedValue + Archive> IndexedDomain<T> {
+    /// Deserializes an archived domain, rebuilding its reverse map.
+    pub fn from_archived<E: Source>(archived: &ArchivedIndexedDomain<T>) -> Result<Self, E>
+    where
+        ArchivedIndexedDomain<T>: Deserialize<Self, ::rkyv::api::high::HighDeserializer<E>>,
+    {
+        ::rkyv::deserialize(archived)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArchivedIndexedDomain;
+    use crate::{test_utils::StrIdx, IndexedDomain};
+    use ::rkyv::rancor::Error;
+
+    #[test]
+    fn test_domain_rkyv() {
+        let domain = IndexedDomain::from_iter([String::from("a"), String::from("b")]);
+        let bytes = ::rkyv::to_bytes::<Error>(&domain).unwrap();
+
+        let archived = ::rkyv::access::<ArchivedIndexedDomain<String>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived.value(StrIdx::from_usize(1)), "b");
+        assert!(archived.try_value(StrIdx::from_usize(2)).is_none());
+
+        let domain2 = IndexedDomain::from_archived::<Error>(archived).unwrap();
+        assert_eq!(domain2.as_vec(), domain.as_vec());
+        assert_eq!(domain2.index(&String::from("b")), StrIdx::from_usize(1));
+    }
+
+    #[test]
+    fn test_domain_rkyv_duplicate() {
+        // A domain archives as a vector of its elements, so a vector can stand in for a corrupt domain.
+        let elems = vec![String::from("a"), String::from("b"), String::from("a")];
+        let bytes = ::rkyv::to_bytes::<Error>(&elems).unwrap();
+        let archived = ::rkyv::access::<ArchivedIndexedDomain<String>, Error>(&bytes).unwrap();
+        let err = IndexedDomain::from_archived::<Error>(archived).unwrap_err();
+        assert!(err.to_string().contains("duplicate"));
+    }
+}
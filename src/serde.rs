@@ -0,0 +1,395 @@
+//! Serialization support via [`serde`](::serde).
+//!
+//! An [`IndexedDomain`] serializes as the sequence of its elements, and deserializes
+//! by rebuilding the reverse map.
+//!
+//! The indexical collections only serialize their contents, not their domain.
+//! Sets and maps store raw indices, so the domain must be saved separately (usually once,
+//! shared by many collections). To deserialize a collection, provide its domain through
+//! [`DeserializeWithDomain`], or equivalently through the [`DomainSeed`] adapter for
+//! use with serde's [`DeserializeSeed`] API.
+//!
+//! ```
+//! # use indexical::{IndexedDomain, serde::DomainSeed, bitset::bitvec::IndexSet};
+//! # use serde::de::DeserializeSeed;
+//! # use std::rc::Rc;
+//! #[derive(PartialEq, Eq, Clone, Hash, Debug)]
+//! pub struct MyString(String);
+//! indexical::define_index_type! {
+//!     pub struct StringIndex for MyString = u32;
+//! }
+//!
+//! let domain = Rc::new(IndexedDomain::from_iter([
+//!     MyString(String::from("Hello")), MyString(String::from("world"))
+//! ]));
+//! let mut set = IndexSet::new(&domain);
+//! set.insert(MyString(String::from("world")));
+//! let json = serde_json::to_string(&set).unwrap();
+//! assert_eq!(json, "[1]");
+//!
+//! let mut de = serde_json::Deserializer::from_str(&json);
+//! let set2: IndexSet<MyString> = DomainSeed::new(&domain).deserialize(&mut de).unwrap();
+//! assert_eq!(set, set2);
+//! ```
+
+use std::{hash::Hash, marker::PhantomData};
+
+use ::serde::{
+    de::{DeserializeSeed, Error},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use index_vec::Idx;
+
+use crate::{
+    bitset::BitSet,
+    map::{DenseIndexMap, SparseIndexMap},
+    pointer::PointerFamily,
+    vec::IndexVec,
+    IndexMatrix, IndexSet, IndexedDomain, IndexedValue,
+};
+
+/// Collections that can be deserialized given the domain they belong to.
+pub trait DeserializeWithDomain: Sized {
+    /// Pointer to the domain of the collection.
+    type Domain;
+
+    /// Deserializes a collection within `domain`.
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error>;
+}
+
+/// A [`DeserializeSeed`] that deserializes a `C` within a given domain.
+pub struct DomainSeed<'d, C: DeserializeWithDomain> {
+    domain: &'d C::Domain,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<'d, C: DeserializeWithDomain> DomainSeed<'d, C> {
+    /// Creates a seed for deserializing a `C` within `domain`.
+    pub fn new(domain: &'d C::Domain) -> Self {
+        DomainSeed {
+            domain,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, C: DeserializeWithDomain> DeserializeSeed<'de> for DomainSeed<'_, C> {
+    type Value = C;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<C, D::Error> {
+        C::deserialize_with_domain(deserializer, self.domain)
+    }
+}
+
+fn check_index<T: IndexedValue, E: Error>(
+    index: usize,
+    domain: &IndexedDomain<T>,
+) -> Result<T::Index, E> {
+    if index < domain.len() {
+        Ok(T::Index::from_usize(index))
+    } else {
+        Err(E::custom(format!(
+            "index {index} out of domain of length {}",
+            domain.len()
+        )))
+    }
+}
+
+fn check_len<E: Error>(len: usize, domain_len: usize) -> Result<(), E> {
+    if len == domain_len {
+        Ok(())
+    } else {
+        Err(E::invalid_length(
+            len,
+            &format!("{domain_len} elements").as_str(),
+        ))
+    }
+}
+
+impl<T: IndexedValue + Serialize> Serialize for IndexedDomain<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: IndexedValue + Deserialize<'de>> Deserialize<'de> for IndexedDomain<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        IndexedDomain::try_from_iter(elems).map_err(|err| {
+            D::Error::custom(format!(
+                "domain contains a duplicate of the element at index {}",
+                err.index.index()
+            ))
+        })
+    }
+}
+
+impl<'a, T, S, P> Serialize for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.indices().map(|idx| idx.index()))
+    }
+}
+
+impl<'a, T, S, P> DeserializeWithDomain for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    type Domain = P::Pointer<IndexedDomain<T>>;
+
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error> {
+        let indices = Vec::<usize>::deserialize(deserializer)?;
+        let mut set = IndexSet::new(domain);
+        for index in indices {
+            set.insert(check_index::<T, D::Error>(index, domain)?);
+        }
+        Ok(set)
+    }
+}
+
+impl<'a, R, C, S, P> Serialize for IndexMatrix<'a, R, C, S, P>
+where
+    R: PartialEq + Eq + Hash + Clone + Serialize,
+    C: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.rows())
+    }
+}
+
+impl<'a, R, C, S, P> DeserializeWithDomain for IndexMatrix<'a, R, C, S, P>
+where
+    R: PartialEq + Eq + Hash + Clone + for<'de> Deserialize<'de>,
+    C: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    type Domain = P::Pointer<IndexedDomain<C>>;
+
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error> {
+        let rows = Vec::<(R, Vec<usize>)>::deserialize(deserializer)?;
+        let mut matrix = IndexMatrix::new(domain);
+        for (row, cols) in rows {
+            let set = matrix.ensure_row(row);
+            for col in cols {
+                set.insert(check_index::<C, D::Error>(col, domain)?);
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+impl<'a, K, V, P> Serialize for SparseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: Serialize,
+    P: PointerFamily<'a>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.into_iter().map(|(idx, v)| (idx.index(), v)))
+    }
+}
+
+impl<'a, K, V, P> DeserializeWithDomain for SparseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: for<'de> Deserialize<'de>,
+    P: PointerFamily<'a>,
+{
+    type Domain = P::Pointer<IndexedDomain<K>>;
+
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error> {
+        let entries = Vec::<(usize, V)>::deserialize(deserializer)?;
+        let mut map = SparseIndexMap::new(domain);
+        for (index, value) in entries {
+            map.insert(check_index::<K, D::Error>(index, domain)?, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<'a, K, V, P> Serialize for DenseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: Serialize,
+    P: PointerFamily<'a>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.map.iter())
+    }
+}
+
+impl<'a, K, V, P> DeserializeWithDomain for DenseIndexMap<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: for<'de> Deserialize<'de>,
+    P: PointerFamily<'a>,
+{
+    type Domain = P::Pointer<IndexedDomain<K>>;
+
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error> {
+        let values = Vec::<Option<V>>::deserialize(deserializer)?;
+        check_len::<D::Error>(values.len(), domain.len())?;
+        let mut map = DenseIndexMap::new(domain);
+        map.map = values.into_iter().collect();
+        Ok(map)
+    }
+}
+
+impl<'a, K, V, P> Serialize for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: Serialize,
+    P: PointerFamily<'a>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'a, K, V, P> DeserializeWithDomain for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: for<'de> Deserialize<'de>,
+    P: PointerFamily<'a>,
+{
+    type Domain = P::Pointer<IndexedDomain<K>>;
+
+    fn deserialize_with_domain<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        domain: &Self::Domain,
+    ) -> Result<Self, D::Error> {
+        let values = Vec::<V>::deserialize(deserializer)?;
+        check_len::<D::Error>(values.len(), domain.len())?;
+        let mut values = values.into_iter();
+        Ok(IndexVec::from_fn(|_| values.next().unwrap(), domain))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DeserializeWithDomain, DomainSeed};
+    use crate::{
+        map::{DenseRcIndexMap, SparseRcIndexMap},
+        test_utils::{TestIndexMatrix, TestIndexSet},
+        vec::RcIndexVec,
+        IndexedDomain,
+    };
+    use ::serde::{de::DeserializeSeed, Serialize};
+    use std::{fmt::Debug, rc::Rc};
+
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    fn domain() -> Rc<IndexedDomain<String>> {
+        Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]))
+    }
+
+    fn round_trip<C>(c: &C, domain: &C::Domain) -> C
+    where
+        C: Serialize + DeserializeWithDomain + PartialEq + Debug,
+    {
+        let json = serde_json::to_string(c).unwrap();
+        let mut de = serde_json::Deserializer::from_str(&json);
+        let c2 = DomainSeed::<C>::new(domain).deserialize(&mut de).unwrap();
+        assert_eq!(c, &c2);
+        c2
+    }
+
+    #[test]
+    fn test_serde_domain() {
+        let d = domain();
+        let json = serde_json::to_string(&*d).unwrap();
+        assert_eq!(json, r#"["a","b","c"]"#);
+        let d2: IndexedDomain<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d2.index(&mk("c")), d.index(&mk("c")));
+
+        let err = serde_json::from_str::<IndexedDomain<String>>(r#"["a","b","a"]"#).unwrap_err();
+        assert!(err.to_string().contains("duplicate"));
+    }
+
+    #[test]
+    fn test_serde_indexset() {
+        let d = domain();
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("a"));
+        s.insert(mk("c"));
+        assert_eq!(serde_json::to_string(&s).unwrap(), "[0,2]");
+        round_trip(&s, &d);
+
+        let mut de = serde_json::Deserializer::from_str("[5]");
+        assert!(TestIndexSet::deserialize_with_domain(&mut de, &d).is_err());
+    }
+
+    #[test]
+    fn test_serde_indexmatrix() {
+        let d = domain();
+        let mut m = TestIndexMatrix::new(&d);
+        m.insert(1, mk("a"));
+        m.insert(1, mk("b"));
+        m.insert(2, mk("c"));
+        let m2 = round_trip(&m, &d);
+        assert_eq!(m2.row(&1).collect::<Vec<_>>(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_serde_maps() {
+        let d = domain();
+        let mut m = SparseRcIndexMap::new(&d);
+        m.insert(mk("b"), 1);
+        let json = serde_json::to_string(&m).unwrap();
+        let mut de = serde_json::Deserializer::from_str(&json);
+        let m2 = DomainSeed::<SparseRcIndexMap<String, i32>>::new(&d)
+            .deserialize(&mut de)
+            .unwrap();
+        assert_eq!(m2.len(), 1);
+        assert_eq!(m2.get(mk("b")), Some(&1));
+
+        let mut m = DenseRcIndexMap::new(&d);
+        m.insert(mk("a"), mk("x"));
+        m.insert(mk("c"), mk("z"));
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, r#"["x",null,"z"]"#);
+        let mut de = serde_json::Deserializer::from_str(&json);
+        let m2 = DomainSeed::<DenseRcIndexMap<String, String>>::new(&d)
+            .deserialize(&mut de)
+            .unwrap();
+        assert_eq!(m2.get(mk("a")), Some(&mk("x")));
+        assert_eq!(m2.get(mk("b")), None);
+        assert_eq!(m2.get(mk("c")), Some(&mk("z")));
+    }
+
+    #[test]
+    fn test_serde_indexvec() {
+        let d = domain();
+        let v = RcIndexVec::from_fn(|idx| d.value(idx).len() as u32, &d);
+        round_trip(&v, &d);
+
+        let mut de = serde_json::Deserializer::from_str("[1, 2]");
+        assert!(RcIndexVec::<String, u32>::deserialize_with_domain(&mut de, &d).is_err());
+    }
+}
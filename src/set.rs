@@ -1,10 +1,18 @@
-use std::fmt;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Range, Sub, SubAssign},
+    rc::Rc,
+};
 
 use index_vec::Idx;
 
 use crate::{
-    bitset::BitSet, pointer::PointerFamily, Captures, FromIndexicalIterator, IndexedDomain,
-    IndexedValue, ToIndex,
+    bitset::BitSet,
+    pointer::{PointerFamily, RcFamily, WeakPointerFamily},
+    Captures, FromIndexicalIterator, IndexRemapping, IndexedDomain, IndexedValue,
+    IndexicalIteratorExt, Join, Meet, ToIndex,
 };
 
 /// An unordered collections of `T`s, implemented with a bit-set.
@@ -27,12 +35,74 @@ where
         }
     }
 
+    /// Creates a set containing every element of the domain, i.e. the top of the [`Meet`] lattice.
+    pub fn top(domain: &P::Pointer<IndexedDomain<T>>) -> Self {
+        let mut set = IndexSet::new(domain);
+        set.insert_all();
+        set
+    }
+
+    /// Returns the union of every set in `sets`, which is empty if there are no sets.
+    pub fn union_all<'s>(
+        domain: &P::Pointer<IndexedDomain<T>>,
+        sets: impl IntoIterator<Item = &'s Self>,
+    ) -> Self
+    where
+        Self: 's,
+    {
+        let mut union = IndexSet::new(domain);
+        for set in sets {
+            union.union(set);
+        }
+        union
+    }
+
+    /// Returns the intersection of every set in `sets`, which is the full domain if there are no sets.
+    pub fn intersect_all<'s>(
+        domain: &P::Pointer<IndexedDomain<T>>,
+        sets: impl IntoIterator<Item = &'s Self>,
+    ) -> Self
+    where
+        Self: 's,
+    {
+        let mut intersection = IndexSet::top(domain);
+        for set in sets {
+            intersection.intersect(set);
+        }
+        intersection
+    }
+
+    /// Wraps a pre-built bit-set `set` as an index set over `domain`.
+    ///
+    /// `set` must have the same size as `domain`. Since [`BitSet`] cannot report its size,
+    /// debug builds only check that every index in `set` is within `domain`.
+    pub fn from_raw(set: S, domain: &P::Pointer<IndexedDomain<T>>) -> Self {
+        let set = IndexSet {
+            set,
+            domain: domain.clone(),
+        };
+        set.assert_within(domain.len());
+        set
+    }
+
     /// Returns an iterator over all the indices contained in `self`.
     #[inline]
     pub fn indices(&self) -> impl Iterator<Item = T::Index> + '_ {
         self.set.iter().map(T::Index::from_usize)
     }
 
+    /// Returns an iterator over all the indices contained in `self`, in descending order.
+    #[inline]
+    pub fn indices_rev(&self) -> impl Iterator<Item = T::Index> + '_ {
+        self.set.iter_rev().map(T::Index::from_usize)
+    }
+
+    /// Returns an iterator over all the objects contained in `self`, in descending index order.
+    #[inline]
+    pub fn iter_rev(&self) -> impl Iterator<Item = &T> + Captures<'a> + '_ {
+        self.indices_rev().map(move |idx| self.domain.value(idx))
+    }
+
     /// Returns an iterator over all the objects contained in `self`.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &T> + Captures<'a> + '_ {
@@ -45,6 +115,83 @@ where
         self.indices().map(move |idx| (idx, self.domain.value(idx)))
     }
 
+    /// Returns an iterator over a projection `f` of the objects contained in `self`.
+    ///
+    /// Equivalent to `self.iter().map(f)`, but with the lifetimes of the result spelled out.
+    #[inline]
+    pub fn iter_map<'s, U>(
+        &'s self,
+        f: impl Fn(&'s T) -> U + 's,
+    ) -> impl Iterator<Item = U> + Captures<'a> + 's {
+        self.iter().map(f)
+    }
+
+    /// Returns an iterator over the objects in `self` but not in `other`, in ascending index order.
+    #[inline]
+    pub fn difference<'s>(
+        &'s self,
+        other: &'s IndexSet<'a, T, S, P>,
+    ) -> impl Iterator<Item = &'s T> + Captures<'a> + 's {
+        self.indices()
+            .filter(move |idx| !other.set.contains(idx.index()))
+            .map(move |idx| self.domain.value(idx))
+    }
+
+    /// Returns an iterator over the objects in both `self` and `other`, in ascending index order.
+    #[inline]
+    pub fn intersection<'s>(
+        &'s self,
+        other: &'s IndexSet<'a, T, S, P>,
+    ) -> impl Iterator<Item = &'s T> + Captures<'a> + 's {
+        self.indices()
+            .filter(move |idx| other.set.contains(idx.index()))
+            .map(move |idx| self.domain.value(idx))
+    }
+
+    /// Returns an iterator over the objects in both `self` and `other`, in ascending index order.
+    ///
+    /// Unlike [`IndexSet::intersection`], this iterates over the smaller of the two sets
+    /// and checks membership in the larger, which is faster when their sizes differ a lot.
+    pub fn iter_intersection<'s>(
+        &'s self,
+        other: &'s IndexSet<'a, T, S, P>,
+    ) -> impl Iterator<Item = &'s T> + Captures<'a> + 's {
+        self.debug_assert_same_domain(other);
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small
+            .indices()
+            .filter(move |idx| large.set.contains(idx.index()))
+            .map(move |idx| self.domain.value(idx))
+    }
+
+    /// Returns an iterator over the objects in exactly one of `self` or `other`,
+    /// in ascending index order.
+    #[inline]
+    pub fn symmetric_difference<'s>(
+        &'s self,
+        other: &'s IndexSet<'a, T, S, P>,
+    ) -> impl Iterator<Item = &'s T> + Captures<'a> + 's {
+        let mut only_self = self
+            .indices()
+            .filter(move |idx| !other.set.contains(idx.index()))
+            .peekable();
+        let mut only_other = other
+            .indices()
+            .filter(move |idx| !self.set.contains(idx.index()))
+            .peekable();
+        // Merge the two ascending sequences so the output stays ascending.
+        std::iter::from_fn(move || match (only_self.peek(), only_other.peek()) {
+            (Some(a), Some(b)) if b < a => only_other.next(),
+            (Some(_), _) => only_self.next(),
+            (None, _) => only_other.next(),
+        })
+        .map(move |idx| self.domain.value(idx))
+    }
+
     /// Returns true if `index` is contained in `self`.
     #[inline]
     pub fn contains<M>(&self, index: impl ToIndex<T, M>) -> bool {
@@ -52,6 +199,38 @@ where
         self.set.contains(elem.index())
     }
 
+    /// Returns true if `self` contains any index in `range`.
+    ///
+    /// `range` must lie within the domain.
+    #[inline]
+    pub fn contains_any_in_range(&self, range: Range<usize>) -> bool {
+        self.set.any_in_range(range)
+    }
+
+    /// Returns the element of `self` with the smallest index, if any.
+    #[inline]
+    pub fn first(&self) -> Option<T::Index> {
+        self.set.first().map(T::Index::from_usize)
+    }
+
+    /// Returns the element of `self` with the largest index, if any.
+    #[inline]
+    pub fn last(&self) -> Option<T::Index> {
+        self.set.last().map(T::Index::from_usize)
+    }
+
+    /// Returns the element of `self` with the `n`-th smallest index (counting from zero), if any.
+    #[inline]
+    pub fn nth(&self, n: usize) -> Option<T::Index> {
+        self.set.nth(n).map(T::Index::from_usize)
+    }
+
+    /// Returns the number of elements of `self` with an index strictly less than `index`.
+    #[inline]
+    pub fn rank<M>(&self, index: impl ToIndex<T, M>) -> usize {
+        self.set.rank(index.to_index(&self.domain).index())
+    }
+
     /// Returns the number of elements in `self`.
     #[inline]
     pub fn len(&self) -> usize {
@@ -67,9 +246,34 @@ where
     /// Returns true if every element in `other` is also in `self`.
     #[inline]
     pub fn is_superset(&self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.debug_assert_same_domain(other);
         self.set.superset(&other.set)
     }
 
+    /// Returns true if every element in `self` is also in `other`.
+    #[inline]
+    pub fn is_subset(&self, other: &IndexSet<'a, T, S, P>) -> bool {
+        other.is_superset(self)
+    }
+
+    /// Returns true if `self` is a subset of `other` and the two sets are not equal.
+    #[inline]
+    pub fn is_proper_subset(&self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.is_subset(other) && self.len() != other.len()
+    }
+
+    /// Returns true if `self` is a superset of `other` and the two sets are not equal.
+    #[inline]
+    pub fn is_proper_superset(&self, other: &IndexSet<'a, T, S, P>) -> bool {
+        other.is_proper_subset(self)
+    }
+
+    /// Returns true if `self` and `other` have no elements in common.
+    #[inline]
+    pub fn is_disjoint(&self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.set.is_disjoint(&other.set)
+    }
+
     /// Adds the element `elt` to `self`, returning true if `self` changed.
     #[inline]
     pub fn insert<M>(&mut self, elt: impl ToIndex<T, M>) -> bool {
@@ -77,42 +281,169 @@ where
         self.set.insert(elt.index())
     }
 
+    /// Adds each element of `iter` to `self`.
+    ///
+    /// Note: this is an inherent method rather than an [`Extend`] impl, since
+    /// the `ToIndex` marker `M` would be unconstrained in the impl.
+    #[inline]
+    pub fn extend<M>(&mut self, iter: impl IntoIterator<Item = impl ToIndex<T, M>>) {
+        for elt in iter {
+            self.insert(elt);
+        }
+    }
+
+    /// Removes the element `elt` from `self`, returning true if `elt` was present.
+    #[inline]
+    pub fn remove<M>(&mut self, elt: impl ToIndex<T, M>) -> bool {
+        let elt = elt.to_index(&self.domain);
+        self.set.remove(elt.index())
+    }
+
+    /// Removes every element `elt` of `self` where `f(elt)` returns false.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        // Collect first, since we can't mutate the bit-set while iterating over it.
+        let to_remove = self
+            .iter_enumerated()
+            .filter(|(_, elt)| !f(elt))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        for idx in to_remove {
+            self.set.remove(idx.index());
+        }
+    }
+
     /// Adds each element of `other` to `self`.
     #[inline]
     pub fn union(&mut self, other: &IndexSet<'a, T, S, P>) {
+        self.debug_assert_same_domain(other);
         self.set.union(&other.set);
     }
 
     /// Adds each element of `other` to `self`, returning true if `self` changed.
     #[inline]
     pub fn union_changed(&mut self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.debug_assert_same_domain(other);
         self.set.union_changed(&other.set)
     }
 
     /// Removes every element of `other` from `self`.
     #[inline]
     pub fn subtract(&mut self, other: &IndexSet<'a, T, S, P>) {
+        self.debug_assert_same_domain(other);
         self.set.subtract(&other.set)
     }
 
     /// Removes every element of `other` from `self`, returning true if `self` changed.
     #[inline]
     pub fn subtract_changed(&mut self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.debug_assert_same_domain(other);
         self.set.subtract_changed(&other.set)
     }
 
     /// Removes every element of `self` not in `other`.
     #[inline]
     pub fn intersect(&mut self, other: &IndexSet<'a, T, S, P>) {
+        self.debug_assert_same_domain(other);
         self.set.intersect(&other.set)
     }
 
     /// Removes every element of `self` not in `other`, returning true if `self` changed.
     #[inline]
     pub fn intersect_changed(&mut self, other: &IndexSet<'a, T, S, P>) -> bool {
+        self.debug_assert_same_domain(other);
         self.set.intersect_changed(&other.set)
     }
 
+    /// Like [`IndexSet::union_changed`], but returns an error if `other` has an incompatible domain.
+    pub fn checked_union(&mut self, other: &IndexSet<'a, T, S, P>) -> Result<bool, DomainMismatch> {
+        self.check_domain(other)?;
+        Ok(self.set.union_changed(&other.set))
+    }
+
+    /// Like [`IndexSet::subtract_changed`], but returns an error if `other` has an incompatible domain.
+    pub fn checked_subtract(
+        &mut self,
+        other: &IndexSet<'a, T, S, P>,
+    ) -> Result<bool, DomainMismatch> {
+        self.check_domain(other)?;
+        Ok(self.set.subtract_changed(&other.set))
+    }
+
+    /// Like [`IndexSet::intersect_changed`], but returns an error if `other` has an incompatible domain.
+    pub fn checked_intersect(
+        &mut self,
+        other: &IndexSet<'a, T, S, P>,
+    ) -> Result<bool, DomainMismatch> {
+        self.check_domain(other)?;
+        Ok(self.set.intersect_changed(&other.set))
+    }
+
+    /// Returns the maximal ranges of consecutive indices in `self`, in ascending order.
+    pub fn to_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for i in self.set.iter() {
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end += 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+        ranges
+    }
+
+    /// Returns the length of the longest range of consecutive indices in `self`.
+    ///
+    /// Along with [`IndexSet::num_runs`], this helps estimate whether a run-length encoded
+    /// backend would be compact.
+    pub fn longest_run(&self) -> usize {
+        self.to_ranges()
+            .into_iter()
+            .map(|range| range.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of maximal ranges of consecutive indices in `self`.
+    pub fn num_runs(&self) -> usize {
+        self.to_ranges().len()
+    }
+
+    /// Returns the number of elements in both `self` and `other`, without allocating their intersection.
+    #[inline]
+    pub fn count_intersection(&self, other: &IndexSet<'a, T, S, P>) -> usize {
+        self.set.count_intersection(&other.set)
+    }
+
+    /// Returns the number of elements in either `self` or `other`, without allocating their union.
+    #[inline]
+    pub fn count_union(&self, other: &IndexSet<'a, T, S, P>) -> usize {
+        self.set.count_union(&other.set)
+    }
+
+    /// Returns the Jaccard similarity of `self` and `other`, i.e. the size of their intersection
+    /// divided by the size of their union, or 0.0 if both are empty.
+    pub fn jaccard(&self, other: &IndexSet<'a, T, S, P>) -> f64 {
+        match self.count_union(other) {
+            0 => 0.0,
+            union => self.count_intersection(other) as f64 / union as f64,
+        }
+    }
+
+    /// Returns the number of elements in exactly one of `self` or `other`.
+    ///
+    /// Useful for measuring how much a dataflow state changed between iterations.
+    pub fn hamming_distance(&self, other: &IndexSet<'a, T, S, P>) -> usize {
+        self.debug_assert_same_domain(other);
+        self.set.count_symmetric_difference(&other.set)
+    }
+
+    /// Returns a new set of the elements in exactly one of `self` or `other`.
+    pub fn changed_bits(&self, other: &IndexSet<'a, T, S, P>) -> IndexSet<'a, T, S, P> {
+        self.debug_assert_same_domain(other);
+        let mut changed = self.clone();
+        changed ^= other;
+        changed
+    }
+
     /// Adds every element of the domain to `self`.
     #[inline]
     pub fn insert_all(&mut self) {
@@ -125,13 +456,212 @@ where
         self.set.clear();
     }
 
+    /// Returns a new set of the elements of the domain not in `self`.
+    pub fn complement(&self) -> IndexSet<'a, T, S, P> {
+        let mut complement = self.clone();
+        complement.set.invert();
+        complement
+    }
+
+    /// Removes every element from `self`, returning an iterator over their indices in ascending order.
+    ///
+    /// The indices are collected up front, so `self` is empty even if the iterator is not consumed.
+    pub fn drain(&mut self) -> impl Iterator<Item = T::Index> + '_ {
+        let indices = self.indices().collect::<Vec<_>>();
+        self.set.clear();
+        indices.into_iter()
+    }
+
+    /// Shrinks the memory used by the inner set as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.set.shrink_to_fit();
+    }
+
+    /// Moves each element of `self` from index `i` to index `i + by`.
+    ///
+    /// A positive `by` shifts towards higher indices, a negative `by` towards lower indices.
+    /// Shifted indices outside `[0, domain.len())` are wrapped modulo the domain size if `wrap`
+    /// is true, and dropped otherwise.
+    pub fn shift(&mut self, by: isize, wrap: bool) {
+        let n = self.domain.len() as isize;
+        let indices = self.set.iter().collect::<Vec<_>>();
+        if indices.is_empty() {
+            return;
+        }
+        self.set.clear();
+
+        // Reduce `by` first so that `i + by` cannot overflow when wrapping.
+        let by = if wrap { by.rem_euclid(n) } else { by };
+        for i in indices {
+            let j = match (i as isize).checked_add(by) {
+                Some(j) if wrap => j.rem_euclid(n),
+                Some(j) if (0..n).contains(&j) => j,
+                _ => continue,
+            };
+            self.set.insert(j as usize);
+        }
+    }
+
+    /// Returns a parallel iterator over all the indices contained in `self`.
+    ///
+    /// The indices are collected up front, so this is only worthwhile when the per-element
+    /// work dominates.
+    #[cfg(feature = "rayon")]
+    pub fn par_indices(&self) -> impl rayon::iter::IndexedParallelIterator<Item = T::Index>
+    where
+        T::Index: Send,
+    {
+        use rayon::iter::IntoParallelIterator;
+        self.indices().collect::<Vec<_>>().into_par_iter()
+    }
+
+    /// Returns a parallel iterator over all the objects contained in `self`.
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = &T> + '_
+    where
+        T: Sync,
+        T::Index: Send + Sync,
+    {
+        use rayon::iter::ParallelIterator;
+        let domain: &IndexedDomain<T> = &self.domain;
+        self.par_indices().map(move |idx| domain.value(idx))
+    }
+
     /// Returns a reference to the inner set.
     #[inline]
     pub fn inner(&self) -> &S {
         &self.set
     }
+
+    /// Returns a mutable reference to the inner set.
+    ///
+    /// Callers must not insert indices outside the domain or change the size of the set.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.set
+    }
+
+    /// Asserts that every index in `self` is less than `bound`.
+    ///
+    /// Only checked in debug builds, e.g. to validate a set after deserialization.
+    #[inline]
+    pub fn assert_within(&self, bound: usize) {
+        if cfg!(debug_assertions) {
+            if let Some(idx) = self.set.iter().find(|idx| *idx >= bound) {
+                panic!("set index {idx} is not within bound {bound}");
+            }
+        }
+    }
+
+    /// Reinterprets `self` as a set over `u_domain`, keeping the same indices.
+    ///
+    /// This is intended for a `u_domain` whose elements correspond index-by-index
+    /// to the elements of `self`'s domain, e.g. one produced by mapping over it.
+    pub fn reinterpret<U: IndexedValue + 'a>(
+        &self,
+        u_domain: &P::Pointer<IndexedDomain<U>>,
+    ) -> IndexSet<'a, U, S, P> {
+        debug_assert_eq!(
+            self.domain.len(),
+            u_domain.len(),
+            "domains have different lengths"
+        );
+        IndexSet {
+            set: self.set.clone(),
+            domain: u_domain.clone(),
+        }
+    }
+
+    /// Translates `self` into a set over `new_domain`, containing `mapping[i]` for each index `i` in `self`.
+    ///
+    /// `mapping` must have an entry for each index of `self`'s domain and map into `new_domain`,
+    /// e.g. a table returned by [`IndexedDomain::union`]. Both are only checked in debug builds.
+    pub fn remap(
+        &self,
+        mapping: &IndexRemapping<T>,
+        new_domain: &P::Pointer<IndexedDomain<T>>,
+    ) -> IndexSet<'a, T, S, P> {
+        debug_assert_eq!(
+            mapping.len(),
+            self.domain.len(),
+            "mapping does not cover the domain"
+        );
+        let mut set = IndexSet::<T, S, P>::new(new_domain);
+        for idx in self.indices() {
+            let new_idx = mapping[idx];
+            debug_assert!(new_idx.index() < new_domain.len());
+            set.set.insert(new_idx.index());
+        }
+        set
+    }
+
+    /// Copies `self` into a set over the same domain backed by a different bit-set `S2`.
+    pub fn convert<S2: BitSet>(&self) -> IndexSet<'a, T, S2, P> {
+        let mut set = S2::empty(self.domain.len());
+        for idx in self.set.iter() {
+            set.insert(idx);
+        }
+        IndexSet {
+            set,
+            domain: self.domain.clone(),
+        }
+    }
+
+    /// Returns a weak reference to the domain of `self`.
+    ///
+    /// Use [`WeakPointerFamily::upgrade`] to recover the domain, e.g. to construct new sets.
+    #[inline]
+    pub fn downgrade_domain(&self) -> P::Weak<IndexedDomain<T>>
+    where
+        P: WeakPointerFamily<'a>,
+    {
+        P::downgrade(&self.domain)
+    }
+
+    /// Domains are compatible if they are the same object, or have the same length.
+    fn check_domain(&self, other: &IndexSet<'a, T, S, P>) -> Result<(), DomainMismatch> {
+        let same = P::ptr_eq(&self.domain, &other.domain);
+        if same || self.domain.len() == other.domain.len() {
+            Ok(())
+        } else {
+            Err(DomainMismatch {
+                expected_len: self.domain.len(),
+                actual_len: other.domain.len(),
+            })
+        }
+    }
+
+    #[inline]
+    pub(crate) fn debug_assert_same_domain(&self, other: &IndexSet<'a, T, S, P>) {
+        debug_assert!(
+            P::ptr_eq(&self.domain, &other.domain),
+            "sets do not share the same domain"
+        );
+    }
+}
+
+/// Error for an operation between two sets with incompatible domains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DomainMismatch {
+    /// Length of the domain of the set being modified.
+    pub expected_len: usize,
+    /// Length of the domain of the other set.
+    pub actual_len: usize,
+}
+
+impl fmt::Display for DomainMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "domain mismatch: expected a domain of length {}, found length {}",
+            self.expected_len, self.actual_len
+        )
+    }
 }
 
+impl std::error::Error for DomainMismatch {}
+
 impl<'a, T, S, P> fmt::Debug for IndexSet<'a, T, S, P>
 where
     T: IndexedValue + fmt::Debug + 'a,
@@ -143,6 +673,10 @@ where
     }
 }
 
+/// Compares only the backing bit-sets, not the domains.
+///
+/// So two sets are equal (and have equal hashes) exactly when they contain the same
+/// indices, which is only meaningful for sets over the same domain.
 impl<'a, T, S, P> PartialEq for IndexSet<'a, T, S, P>
 where
     T: IndexedValue + 'a,
@@ -162,6 +696,22 @@ where
 {
 }
 
+/// Hashes the contents of the backing bit-set via [`BitSet::hash_words`].
+///
+/// Only the bit-set is hashed, not the domain, so hashes are meaningful
+/// only between sets over the same domain. Hashes are also not portable across
+/// backends, since dense backends hash their raw words.
+impl<'a, T, S, P> Hash for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.set.hash_words(state);
+    }
+}
+
 impl<'a, T, S, P> Clone for IndexSet<'a, T, S, P>
 where
     T: IndexedValue + 'a,
@@ -181,6 +731,28 @@ where
     }
 }
 
+impl<'a, T, S, P> Join for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn join(&mut self, other: &Self) -> bool {
+        self.union_changed(other)
+    }
+}
+
+impl<'a, T, S, P> Meet for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    fn meet(&mut self, other: &Self) -> bool {
+        self.intersect_changed(other)
+    }
+}
+
 impl<'a, T, U, S, M, P> FromIndexicalIterator<'a, T, P, M, U> for IndexSet<'a, T, S, P>
 where
     T: IndexedValue + 'a,
@@ -200,10 +772,168 @@ where
     }
 }
 
+macro_rules! impl_set_op {
+    ($op:ident, $op_fn:ident, $op_assign:ident, $op_assign_fn:ident, $method:ident) => {
+        impl<'a, T, S, P> $op for &IndexSet<'a, T, S, P>
+        where
+            T: IndexedValue + 'a,
+            S: BitSet,
+            P: PointerFamily<'a>,
+        {
+            type Output = IndexSet<'a, T, S, P>;
+
+            fn $op_fn(self, rhs: Self) -> Self::Output {
+                self.debug_assert_same_domain(rhs);
+                let mut set = self.clone();
+                set.set.$method(&rhs.set);
+                set
+            }
+        }
+
+        impl<'a, T, S, P> $op_assign<&IndexSet<'a, T, S, P>> for IndexSet<'a, T, S, P>
+        where
+            T: IndexedValue + 'a,
+            S: BitSet,
+            P: PointerFamily<'a>,
+        {
+            fn $op_assign_fn(&mut self, rhs: &IndexSet<'a, T, S, P>) {
+                self.debug_assert_same_domain(rhs);
+                self.set.$method(&rhs.set);
+            }
+        }
+    };
+}
+
+impl_set_op!(BitOr, bitor, BitOrAssign, bitor_assign, union);
+impl_set_op!(BitAnd, bitand, BitAndAssign, bitand_assign, intersect);
+impl_set_op!(Sub, sub, SubAssign, sub_assign, subtract);
+impl_set_op!(
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    symmetric_difference
+);
+
+/// Builds an [`IndexedDomain`] and an [`IndexSet`] over it in a single pass.
+///
+/// Each inserted value is interned into the domain (if it is new) and added to the set.
+/// Because the domain keeps growing during construction, the set itself is only
+/// materialized by [`SetBuilder::finish`] once the domain's final size is known.
+pub struct SetBuilder<T: IndexedValue, S: BitSet> {
+    domain: IndexedDomain<T>,
+    indices: Vec<T::Index>,
+    _set: PhantomData<S>,
+}
+
+impl<T: IndexedValue, S: BitSet> SetBuilder<T, S> {
+    /// Creates a builder with an empty domain.
+    pub fn new() -> Self {
+        Self::from_domain(IndexedDomain::from_iter([]))
+    }
+
+    /// Creates a builder that extends an existing `domain`.
+    pub fn from_domain(domain: IndexedDomain<T>) -> Self {
+        SetBuilder {
+            domain,
+            indices: Vec::new(),
+            _set: PhantomData,
+        }
+    }
+
+    /// Interns `value` into the domain and adds it to the set, returning its index.
+    pub fn insert_value(&mut self, value: T) -> T::Index {
+        let idx = if self.domain.contains(&value) {
+            self.domain.index(&value)
+        } else {
+            self.domain.insert(value)
+        };
+        self.indices.push(idx);
+        idx
+    }
+
+    /// Consumes the builder, returning the domain and the set of inserted values.
+    pub fn finish(self) -> (Rc<IndexedDomain<T>>, IndexSet<'static, T, S, RcFamily>) {
+        let domain = Rc::new(self.domain);
+        let set = self.indices.into_iter().collect_indexical(&domain);
+        (domain, set)
+    }
+}
+
+impl<T: IndexedValue, S: BitSet> Default for SetBuilder<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An owning iterator over the objects of an [`IndexSet`].
+///
+/// Because the domain is shared, each object is cloned out of the domain
+/// as the iterator advances.
+pub struct IndexSetIntoIter<'a, T: IndexedValue + 'a, P: PointerFamily<'a>> {
+    indices: std::vec::IntoIter<T::Index>,
+    domain: P::Pointer<IndexedDomain<T>>,
+}
+
+impl<'a, T, P> Iterator for IndexSetIntoIter<'a, T, P>
+where
+    T: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.indices.next()?;
+        Some(self.domain.value(idx).clone())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<'a, T, P> ExactSizeIterator for IndexSetIntoIter<'a, T, P>
+where
+    T: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+}
+
+impl<'a, T, S, P> IntoIterator for IndexSet<'a, T, S, P>
+where
+    T: IndexedValue + 'a,
+    S: BitSet,
+    P: PointerFamily<'a>,
+{
+    type Item = T;
+    type IntoIter = IndexSetIntoIter<'a, T, P>;
+
+    /// Consumes `self`, yielding a clone of each object in ascending index order.
+    fn into_iter(self) -> Self::IntoIter {
+        let indices = self.indices().collect::<Vec<_>>();
+        IndexSetIntoIter {
+            indices: indices.into_iter(),
+            domain: self.domain,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{test_utils::TestIndexSet, IndexedDomain, IndexicalIteratorExt};
-    use std::rc::Rc;
+    use super::{DomainMismatch, SetBuilder};
+    use crate::{
+        bitset::bitvec::BitVec,
+        pointer::{RcFamily, WeakPointerFamily},
+        test_utils::TestIndexSet,
+        IndexedDomain, IndexicalIteratorExt, Join, Meet,
+    };
+    use std::{
+        collections::{hash_map::DefaultHasher, HashSet},
+        hash::{Hash, Hasher},
+        rc::Rc,
+    };
 
     fn mk(s: &str) -> String {
         s.to_string()
@@ -226,7 +956,307 @@ mod test {
                 .collect_indexical::<TestIndexSet<_>>(&d),
             s
         );
-        assert_eq!(format!("{s:?}"), r#"{"a", "b"}"#)
+        assert_eq!(format!("{s:?}"), r#"{"a", "b"}"#);
+
+        assert!(s.remove(mk("a")));
+        assert!(!s.remove(mk("a")));
+        assert!(!s.remove(mk("c")));
+        assert!(!s.contains(mk("a")));
+        assert!(s.contains(b));
+        assert_eq!(s.len(), 1);
+    }
+
+    #[test]
+    fn test_indexset_is_disjoint() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s1 = [mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = [mk("b"), mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s3 = [mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert!(!s1.is_disjoint(&s2));
+        assert!(s1.is_disjoint(&s3));
+        assert!(s3.is_disjoint(&s1));
+        assert!(s1.is_disjoint(&TestIndexSet::new(&d)));
+    }
+
+    #[test]
+    fn test_indexset_hamming_distance() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let prev = [mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let cur = [mk("a"), mk("c"), mk("d")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert_eq!(cur.hamming_distance(&prev), 3);
+        assert_eq!(prev.hamming_distance(&cur), 3);
+        assert_eq!(cur.hamming_distance(&cur), 0);
+
+        let changed = cur.changed_bits(&prev);
+        assert_eq!(changed.iter().collect::<Vec<_>>(), vec!["b", "c", "d"]);
+        assert_eq!(changed.len(), cur.hamming_distance(&prev));
+    }
+
+    #[test]
+    fn test_indexset_ops() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s1 = [mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = [mk("b"), mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let elems = |s: &TestIndexSet<String>| s.iter().cloned().collect::<Vec<_>>();
+
+        assert_eq!(elems(&(&s1 | &s2)), vec![mk("a"), mk("b"), mk("c")]);
+        assert_eq!(elems(&(&s1 & &s2)), vec![mk("b")]);
+        assert_eq!(elems(&(&s1 - &s2)), vec![mk("a")]);
+        assert_eq!(elems(&(&s1 ^ &s2)), vec![mk("a"), mk("c")]);
+        assert_eq!(elems(&s1), vec![mk("a"), mk("b")]);
+
+        let mut s = s1.clone();
+        s |= &s2;
+        assert_eq!(s, &s1 | &s2);
+        let mut s = s1.clone();
+        s &= &s2;
+        assert_eq!(s, &s1 & &s2);
+        let mut s = s1.clone();
+        s -= &s2;
+        assert_eq!(s, &s1 - &s2);
+        let mut s = s1.clone();
+        s ^= &s2;
+        assert_eq!(s, &s1 ^ &s2);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "sets do not share the same domain")]
+    fn test_indexset_ops_domain_mismatch() {
+        let d1 = Rc::new(IndexedDomain::from_iter([mk("a")]));
+        let d2 = Rc::new(IndexedDomain::from_iter([mk("a")]));
+        let _ = &TestIndexSet::new(&d1) | &TestIndexSet::new(&d2);
+    }
+
+    #[test]
+    fn test_indexset_assert_within() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("b"));
+        s.assert_within(2);
+    }
+
+    #[test]
+    fn test_indexset_from_raw() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("b"));
+
+        let mut raw = s.inner().clone();
+        raw.set(2, true);
+        let mut s2 = TestIndexSet::from_raw(raw, &d);
+        assert_eq!(s2.iter().collect::<Vec<_>>(), vec!["b", "c"]);
+
+        s2.inner_mut().set(1, false);
+        assert_eq!(s2.iter().collect::<Vec<_>>(), vec!["c"]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "set index 2 is not within bound 2")]
+    fn test_indexset_assert_within_fails() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("c"));
+        s.assert_within(2);
+    }
+
+    #[test]
+    fn test_indexset_iter_set_ops() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+            mk("e"),
+        ]));
+        let s1 = [mk("d"), mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = [mk("e"), mk("b"), mk("c"), mk("d")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+
+        assert_eq!(s1.difference(&s2).collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(s2.difference(&s1).collect::<Vec<_>>(), vec!["c", "e"]);
+        assert_eq!(s1.intersection(&s2).collect::<Vec<_>>(), vec!["b", "d"]);
+        assert_eq!(
+            s1.symmetric_difference(&s2).collect::<Vec<_>>(),
+            vec!["a", "c", "e"]
+        );
+        assert_eq!(
+            s2.symmetric_difference(&s1).collect::<Vec<_>>(),
+            vec!["a", "c", "e"]
+        );
+    }
+
+    #[test]
+    fn test_set_builder() {
+        let mut builder = SetBuilder::<String, BitVec>::new();
+        for s in ["b", "a", "b", "c", "a"] {
+            builder.insert_value(mk(s));
+        }
+        let (d, s) = builder.finish();
+        assert_eq!(d.iter().collect::<Vec<_>>(), vec!["b", "a", "c"]);
+        assert_eq!(s.len(), 3);
+        assert!(s.contains(mk("a")));
+
+        let d = IndexedDomain::from_iter([mk("x"), mk("y")]);
+        let mut builder = SetBuilder::<String, BitVec>::from_domain(d);
+        builder.insert_value(mk("y"));
+        builder.insert_value(mk("z"));
+        let (d, s) = builder.finish();
+        assert_eq!(d.len(), 3);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec!["y", "z"]);
+    }
+
+    #[test]
+    fn test_indexset_retain() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert_all();
+        s.retain(|elt| d.index(elt).index() % 2 == 0);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_indexset_contains_any_in_range() {
+        let d = Rc::new(IndexedDomain::from_iter((0..30).map(|i| i.to_string())));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("5"));
+        s.insert(mk("25"));
+        assert!(s.contains_any_in_range(0..6));
+        assert!(s.contains_any_in_range(5..6));
+        assert!(!s.contains_any_in_range(6..25));
+        assert!(!s.contains_any_in_range(10..20));
+        assert!(s.contains_any_in_range(10..30));
+        assert!(!s.contains_any_in_range(5..5));
+    }
+
+    #[test]
+    fn test_indexset_extend() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("a"));
+        s.extend(vec![mk("b"), mk("a")]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+        s.extend(vec![d.index(&mk("d"))]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec!["a", "b", "d"]);
+    }
+
+    #[test]
+    fn test_indexset_downgrade_domain() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let s = TestIndexSet::new(&d);
+        let weak = s.downgrade_domain();
+        drop(d);
+
+        let d = RcFamily::upgrade(&weak).unwrap();
+        let mut s2 = TestIndexSet::new(&d);
+        s2.insert(mk("b"));
+        assert!(s2.contains(mk("b")));
+
+        drop((s, s2, d));
+        assert!(RcFamily::upgrade(&weak).is_none());
+    }
+
+    #[test]
+    fn test_indexset_subset() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let ab = [mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let a = [mk("a")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let c = [mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+
+        assert!(ab.is_subset(&ab));
+        assert!(!ab.is_proper_subset(&ab));
+        assert!(!ab.is_proper_superset(&ab));
+
+        assert!(a.is_subset(&ab));
+        assert!(a.is_proper_subset(&ab));
+        assert!(ab.is_proper_superset(&a));
+        assert!(!ab.is_subset(&a));
+
+        assert!(!c.is_subset(&ab));
+        assert!(!ab.is_superset(&c));
+        assert!(!c.is_proper_subset(&ab));
+    }
+
+    #[test]
+    fn test_indexset_reinterpret() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("bb"), mk("ccc")]));
+        let lens = Rc::new(d.iter().map(|s| s.len()).collect::<IndexedDomain<_>>());
+        let s = [mk("a"), mk("ccc")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = s.reinterpret(&lens);
+        assert_eq!(s2.iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+        assert!(s2.contains(3));
+    }
+
+    #[test]
+    fn test_indexset_hash() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s1 = [mk("a"), mk("c")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let mut s2 = TestIndexSet::new(&d);
+        s2.insert(mk("c"));
+        s2.insert(mk("a"));
+
+        let hash = |s: &TestIndexSet<String>| {
+            let mut hasher = DefaultHasher::new();
+            s.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&s1), hash(&s2));
+
+        let states = HashSet::from([s1, s2, TestIndexSet::new(&d)]);
+        assert_eq!(states.len(), 2);
+    }
+
+    #[test]
+    fn test_indexset_into_iter() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s = [mk("c"), mk("a")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let elems = s.into_iter().collect::<Vec<_>>();
+        assert_eq!(elems, vec![mk("a"), mk("c")]);
     }
 
     #[cfg(feature = "bitvec")]
@@ -240,4 +1270,333 @@ mod test {
         let s2 = s.clone();
         assert!(std::ptr::eq(s.domain, s2.domain));
     }
+
+    #[test]
+    fn test_indexset_shift() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let s = [mk("a"), mk("c"), mk("d")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+
+        let mut dropped = s.clone();
+        dropped.shift(1, false);
+        assert_eq!(dropped.iter().collect::<Vec<_>>(), vec!["b", "d"]);
+        dropped.shift(-2, false);
+        assert_eq!(dropped.iter().collect::<Vec<_>>(), vec!["b"]);
+
+        let mut wrapped = s.clone();
+        wrapped.shift(1, true);
+        assert_eq!(wrapped.iter().collect::<Vec<_>>(), vec!["a", "b", "d"]);
+        wrapped.shift(-6, true);
+        assert_eq!(wrapped.iter().collect::<Vec<_>>(), vec!["b", "c", "d"]);
+
+        // isize::MAX is 3 mod 4.
+        wrapped.shift(isize::MAX, true);
+        assert_eq!(wrapped.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        wrapped.shift(isize::MIN, true);
+        assert_eq!(wrapped.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+        let mut overflowed = s.clone();
+        overflowed.shift(isize::MAX, false);
+        assert!(overflowed.is_empty());
+        let mut overflowed = s.clone();
+        overflowed.shift(isize::MIN, false);
+        assert!(overflowed.is_empty());
+    }
+
+    #[test]
+    fn test_indexset_count_intersection() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s1: TestIndexSet<_> = [mk("a"), mk("b")].into_iter().collect_indexical(&d);
+        let s2: TestIndexSet<_> = [mk("b"), mk("c")].into_iter().collect_indexical(&d);
+        assert_eq!(s1.count_intersection(&s2), (&s1 & &s2).len());
+        assert_eq!(s1.count_intersection(&s2), 1);
+        assert_eq!(s1.count_intersection(&TestIndexSet::new(&d)), 0);
+    }
+
+    #[test]
+    fn test_indexset_jaccard() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let set =
+            |elts: &[&str]| -> TestIndexSet<_> { elts.iter().map(|s| mk(s)).collect_indexical(&d) };
+        let (ab, cd, bcd) = (set(&["a", "b"]), set(&["c", "d"]), set(&["b", "c", "d"]));
+        assert_eq!(ab.count_union(&bcd), 4);
+        assert_eq!(ab.jaccard(&cd), 0.0);
+        assert_eq!(ab.jaccard(&ab), 1.0);
+        assert_eq!(ab.jaccard(&bcd), 0.25);
+        assert_eq!(set(&[]).jaccard(&set(&[])), 0.0);
+    }
+
+    #[test]
+    fn test_indexset_complement() {
+        let d = Rc::new(IndexedDomain::from_iter(0..4usize));
+        let s: TestIndexSet<_> = [0, 2].into_iter().collect_indexical(&d);
+        let c = s.complement();
+        assert_eq!(c.iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(c.len(), 2);
+        assert_eq!(s.len(), 2);
+        assert_eq!(c.complement(), s);
+    }
+
+    #[test]
+    fn test_indexset_iter_map() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("bbb"), mk("cc")]));
+        let s: TestIndexSet<_> = [mk("bbb"), mk("cc")].into_iter().collect_indexical(&d);
+        let lens = s.iter_map(|s| s.len()).collect::<Vec<_>>();
+        assert_eq!(lens, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_indexset_join_fixpoint() {
+        // Reaching nodes in the graph 0 -> 1 -> 2 -> 1, 2 -> 3.
+        let d = Rc::new(IndexedDomain::from_iter(0..4usize));
+        let edges = [(0, 1), (1, 2), (2, 1), (2, 3)];
+        let mut states = (0..4).map(|_| TestIndexSet::new(&d)).collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(src, dst) in &edges {
+                let mut out = states[src].clone();
+                out.insert(src);
+                changed |= states[dst].join(&out);
+            }
+        }
+
+        let reached = |n: usize| states[n].iter().copied().collect::<Vec<_>>();
+        assert_eq!(reached(0), Vec::<usize>::new());
+        assert_eq!(reached(1), vec![0, 1, 2]);
+        assert_eq!(reached(3), vec![0, 1, 2]);
+        assert!(!states[3].clone().join(&states[2]));
+    }
+
+    #[test]
+    fn test_indexset_meet() {
+        let d = Rc::new(IndexedDomain::from_iter(0..70usize));
+        let mut s = TestIndexSet::top(&d);
+        assert_eq!(s.len(), 70);
+        assert_eq!(s.iter().last(), Some(&69));
+
+        let t: TestIndexSet<_> = [1, 3, 65].into_iter().collect_indexical(&d);
+        assert!(s.meet(&t));
+        assert_eq!(s, t);
+        assert!(!s.meet(&t));
+        assert!(!s.meet(&TestIndexSet::top(&d)));
+
+        let u: TestIndexSet<_> = [3, 4, 65].into_iter().collect_indexical(&d);
+        assert!(s.meet(&u));
+        assert_eq!(s.iter().copied().collect::<Vec<_>>(), vec![3, 65]);
+    }
+
+    #[test]
+    fn test_indexset_drain() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("c"));
+        s.insert(mk("a"));
+
+        let drained = s.drain().collect::<Vec<_>>();
+        assert_eq!(drained, vec![d.index(&mk("a")), d.index(&mk("c"))]);
+        assert!(s.is_empty());
+
+        s.insert(mk("b"));
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_indexset_union_intersect_all() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let s1 = [mk("a"), mk("b")].into_iter().collect_indexical(&d);
+        let s2 = [mk("b"), mk("c")].into_iter().collect_indexical(&d);
+        let sets: [TestIndexSet<_>; 2] = [s1, s2];
+
+        let union = TestIndexSet::union_all(&d, &sets);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        let intersection = TestIndexSet::intersect_all(&d, &sets);
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), vec!["b"]);
+
+        assert!(TestIndexSet::union_all(&d, []).is_empty());
+        assert_eq!(TestIndexSet::intersect_all(&d, []).len(), 3);
+    }
+
+    #[test]
+    fn test_indexset_remap() {
+        let d1 = IndexedDomain::from_iter([mk("a"), mk("b")]);
+        let d2 = IndexedDomain::from_iter([mk("c"), mk("b")]);
+        let (d, _, map2) = d1.union(&d2);
+        let (d2, d) = (Rc::new(d2), Rc::new(d));
+
+        let mut s = TestIndexSet::new(&d2);
+        s.insert(mk("c"));
+        s.insert(mk("b"));
+        let s2 = s.remap(&map2, &d);
+        assert_eq!(s2.iter().collect::<Vec<_>>(), vec!["b", "c"]);
+        assert!(!s2.contains(mk("a")));
+    }
+
+    #[cfg(all(feature = "bitvec", feature = "roaring"))]
+    #[test]
+    fn test_indexset_convert() {
+        use crate::bitset::roaring::RoaringSet;
+
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s = TestIndexSet::new(&d);
+        s.insert(mk("a"));
+        s.insert(mk("c"));
+
+        let s2 = s.convert::<RoaringSet>();
+        assert!(Rc::ptr_eq(&d, &s2.domain));
+        assert_eq!(s2.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+        assert_eq!(s2.convert::<BitVec>(), s);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_indexset_par_iter() {
+        use rayon::prelude::*;
+
+        let d = Rc::new(IndexedDomain::from_iter(0..1000usize));
+        let s = (0..1000usize)
+            .filter(|i| i % 3 == 0)
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let serial = s.iter().sum::<usize>();
+        assert_eq!(s.par_iter().sum::<usize>(), serial);
+        assert_eq!(
+            s.par_indices().map(|idx| idx.index()).sum::<usize>(),
+            serial
+        );
+    }
+
+    #[test]
+    fn test_indexset_checked_ops() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut s1 = [mk("a")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = [mk("a"), mk("b")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert_eq!(s1.checked_union(&s2), Ok(true));
+        assert_eq!(s1.checked_union(&s2), Ok(false));
+        assert_eq!(s1.checked_subtract(&s2), Ok(true));
+        assert!(s1.is_empty());
+
+        let d_same_len = Rc::new(IndexedDomain::from_iter([mk("x"), mk("y"), mk("z")]));
+        let s3 = [mk("y")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d_same_len);
+        let mut s4 = s2.clone();
+        assert_eq!(s4.checked_intersect(&s3), Ok(true));
+        assert_eq!(s4.iter().collect::<Vec<_>>(), vec!["b"]);
+
+        let d_short = Rc::new(IndexedDomain::from_iter([mk("a")]));
+        let s5 = TestIndexSet::new(&d_short);
+        let mismatch = DomainMismatch {
+            expected_len: 3,
+            actual_len: 1,
+        };
+        let mut s6 = s2.clone();
+        assert_eq!(s6.checked_union(&s5), Err(mismatch));
+        assert_eq!(s6.checked_subtract(&s5), Err(mismatch));
+        assert_eq!(s6.checked_intersect(&s5), Err(mismatch));
+        assert_eq!(s6, s2);
+    }
+
+    #[test]
+    fn test_indexset_iter_intersection() {
+        let d = Rc::new(IndexedDomain::from_iter((0..100).map(|i| i.to_string())));
+        let s1 = (0..100)
+            .filter(|i| i % 2 == 0)
+            .map(|i| i.to_string())
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        let s2 = [mk("4"), mk("7"), mk("50")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+
+        let mut expected = s1.clone();
+        expected.intersect(&s2);
+        let expected = expected.iter().collect::<Vec<_>>();
+        assert_eq!(expected, vec!["4", "50"]);
+        assert_eq!(s1.iter_intersection(&s2).collect::<Vec<_>>(), expected);
+        assert_eq!(s2.iter_intersection(&s1).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_indexset_runs() {
+        let d = Rc::new(IndexedDomain::from_iter(0..10usize));
+        let s = [0, 1, 2, 5usize]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert_eq!(s.to_ranges(), vec![0..3, 5..6]);
+        assert_eq!(s.longest_run(), 3);
+        assert_eq!(s.num_runs(), 2);
+
+        let empty = TestIndexSet::new(&d);
+        assert_eq!(empty.longest_run(), 0);
+        assert_eq!(empty.num_runs(), 0);
+    }
+
+    #[test]
+    fn test_indexset_iter_rev() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let s = [mk("a"), mk("c"), mk("d")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert_eq!(s.iter_rev().collect::<Vec<_>>(), vec!["d", "c", "a"]);
+        assert_eq!(
+            s.indices_rev().map(|idx| idx.index()).collect::<Vec<_>>(),
+            vec![3, 2, 0]
+        );
+        assert_eq!(TestIndexSet::new(&d).iter_rev().count(), 0);
+    }
+
+    #[test]
+    fn test_indexset_first_last() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let mut s = TestIndexSet::new(&d);
+        assert_eq!((s.first(), s.last()), (None, None));
+        s.insert(mk("b"));
+        s.insert(mk("c"));
+        assert_eq!(s.first(), Some(d.index(&mk("b"))));
+        assert_eq!(s.last(), Some(d.index(&mk("c"))));
+    }
+
+    #[test]
+    fn test_indexset_nth_rank() {
+        let d = Rc::new(IndexedDomain::from_iter([
+            mk("a"),
+            mk("b"),
+            mk("c"),
+            mk("d"),
+        ]));
+        let s = [mk("b"), mk("d")]
+            .into_iter()
+            .collect_indexical::<TestIndexSet<_>>(&d);
+        assert_eq!(s.nth(0), Some(d.index(&mk("b"))));
+        assert_eq!(s.nth(1), Some(d.index(&mk("d"))));
+        assert_eq!(s.nth(2), None);
+        assert_eq!(
+            d.iter().map(|x| s.rank(x)).collect::<Vec<_>>(),
+            vec![0, 0, 1, 1]
+        );
+    }
 }
@@ -1,9 +1,33 @@
 use crate::{bitset::BitSet, define_index_type};
+use std::{collections::hash_map::DefaultHasher, hash::Hasher};
 
 define_index_type! {
   pub struct StrIdx for String = u32;
 }
 
+define_index_type! {
+  pub struct UsizeIdx for usize = u32;
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Wrapper<T>(pub T);
+
+define_index_type! {
+  pub struct WrapperIdx for Wrapper<T> where T: Clone + Eq + std::hash::Hash = u32;
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Labeled<'a, K, V>(pub &'a K, pub V);
+
+define_index_type! {
+  pub struct LabeledIdx for Labeled<'a, K, V>
+  where
+    K: Clone + Eq + std::hash::Hash,
+    V: Clone + Eq + std::hash::Hash,
+  = u32;
+  DEBUG_FORMAT = "L{}";
+}
+
 pub type TestIndexSet<T> = crate::bitset::bitvec::IndexSet<T>;
 pub type TestIndexMatrix<R, C> = crate::bitset::bitvec::IndexMatrix<R, C>;
 
@@ -11,7 +35,8 @@ pub fn impl_test<T: BitSet>() {
     let mut bv = T::empty(10);
     assert!(!bv.contains(0));
 
-    bv.insert(0);
+    assert!(bv.insert(0));
+    assert!(!bv.insert(0));
     bv.insert(5);
     assert!(bv.contains(0));
     assert!(bv.contains(5));
@@ -29,8 +54,21 @@ pub fn impl_test<T: BitSet>() {
     assert!(!bv.intersect_changed(&bv2));
     assert_eq!(bv.iter().collect::<Vec<_>>(), vec![5]);
 
+    assert!(bv.remove(5));
+    assert!(!bv.remove(5));
+    assert!(!bv.remove(3));
+    assert_eq!(bv.iter().collect::<Vec<_>>(), Vec::<usize>::new());
+
     let mut bv = T::empty(64 * 4 + 1);
     bv.insert(64 * 4);
+    bv.insert(70);
+    assert!(bv.any_in_range(0..71));
+    assert!(bv.any_in_range(70..71));
+    assert!(!bv.any_in_range(0..70));
+    assert!(!bv.any_in_range(71..64 * 4));
+    assert!(bv.any_in_range(71..64 * 4 + 1));
+    assert!(!bv.any_in_range(3..3));
+    bv.remove(70);
     assert!(!bv.contains(64 * 4 - 1));
     assert!(bv.contains(64 * 4));
 
@@ -41,13 +79,122 @@ pub fn impl_test<T: BitSet>() {
     bv2.insert(0);
     bv.subtract(&bv2);
     assert_eq!(bv.iter().collect::<Vec<_>>(), vec![1]);
+    bv.insert(5);
+    assert_eq!(bv.iter_rev().collect::<Vec<_>>(), vec![5, 1]);
+    assert_eq!((bv.first(), bv.last()), (Some(1), Some(5)));
+    assert_eq!((T::empty(10).first(), T::empty(10).last()), (None, None));
+    bv.remove(5);
+    let mut bv3 = bv.clone();
+    bv3.insert(0);
+    assert!(bv3.subtract_changed(&bv2));
+    assert!(!bv3.subtract_changed(&bv2));
+    assert_eq!(bv3.iter().collect::<Vec<_>>(), vec![1]);
+
+    let mut bv2 = T::empty(10);
+    bv2.insert(1);
+    bv2.insert(2);
+    assert!(bv.symmetric_difference_changed(&bv2));
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![2]);
+    assert!(!bv.symmetric_difference_changed(&T::empty(10)));
+    bv.symmetric_difference(&bv2);
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![1]);
 
     bv.invert();
     assert_eq!(
         bv.iter().collect::<Vec<_>>(),
         vec![0, 2, 3, 4, 5, 6, 7, 8, 9]
     );
+    assert_eq!((bv.first(), bv.last()), (Some(0), Some(9)));
+
+    let mut bv2 = T::empty(10);
+    bv2.insert(2);
+    bv2.insert(4);
+    assert!(!bv.is_disjoint(&bv2));
+    bv2.clear();
+    bv2.insert(1);
+    assert!(bv.is_disjoint(&bv2));
 
     bv.clear();
     assert_eq!(bv.iter().collect::<Vec<_>>(), Vec::<usize>::new());
+
+    let mut bv = T::empty(200);
+    for i in [0, 3, 64, 65, 130, 199] {
+        bv.insert(i);
+    }
+    let ones = bv.iter().collect::<Vec<_>>();
+    for n in 0..ones.len() + 1 {
+        assert_eq!(bv.nth(n), ones.get(n).copied());
+    }
+    for i in 0..=200 {
+        assert_eq!(bv.rank(i), ones.iter().filter(|j| **j < i).count());
+    }
+
+    let mut bv = T::empty(10);
+    bv.insert(3);
+    bv.insert(9);
+    bv.grow(300);
+    assert!(!bv.contains(10) && !bv.contains(299));
+    assert!(bv.insert(299));
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![3, 9, 299]);
+    bv.truncate(9);
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![3]);
+    bv.grow(300);
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![3]);
+    bv.invert();
+    bv.truncate(5);
+    bv.grow(10);
+    assert_eq!(bv.iter().collect::<Vec<_>>(), vec![0, 1, 2, 4]);
+    assert_eq!(bv.len(), 4);
+
+    let mut a = T::empty(200);
+    let mut b = T::empty(200);
+    for i in [0, 5, 64, 100, 150, 199] {
+        a.insert(i);
+    }
+    for i in [5, 63, 64, 150, 198] {
+        b.insert(i);
+    }
+    let count_clone = |a: &T, b: &T| {
+        let mut a = a.clone();
+        a.intersect(b);
+        a.len()
+    };
+    assert_eq!(a.count_intersection(&b), count_clone(&a, &b));
+    assert_eq!(a.count_intersection(&b), 3);
+    assert_eq!(a.count_union(&b), 8);
+    assert_eq!(a.count_union(&T::empty(200)), 6);
+    assert_eq!(a.count_symmetric_difference(&b), 5);
+    assert_eq!(b.count_symmetric_difference(&a), 5);
+    assert_eq!(a.count_symmetric_difference(&a), 0);
+    a.invert();
+    b.invert();
+    assert_eq!(a.count_intersection(&b), 200 - 8);
+    assert_eq!(T::empty(200).count_intersection(&b), 0);
+
+    let n = 100_000;
+    let mut big = T::empty(n);
+    let mut small = T::empty(n);
+    for i in (0..n).step_by(3) {
+        big.insert(i);
+        if i % 2 == 0 {
+            small.insert(i);
+        }
+    }
+    for _ in 0..10 {
+        assert!(big.superset(&small));
+        assert!(!small.superset(&big));
+    }
+
+    let hash = |bv: &T| {
+        let mut hasher = DefaultHasher::new();
+        bv.hash_words(&mut hasher);
+        hasher.finish()
+    };
+    let mut bv = T::empty(10);
+    bv.insert(3);
+    bv.insert(7);
+    let mut bv2 = T::empty(10);
+    bv2.insert(7);
+    bv2.insert(3);
+    assert_eq!(hash(&bv), hash(&bv2));
 }
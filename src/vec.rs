@@ -0,0 +1,601 @@
+//! Vector-like collections for indexed keys.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use index_vec::Idx;
+
+use crate::{
+    map::{DenseIndexMap, SparseIndexMap},
+    pointer::{ArcFamily, PointerFamily, RcFamily, RefFamily},
+    Captures, FromIndexicalIterator, IndexedDomain, IndexedValue, MarkerOwned, ToIndex,
+};
+
+/// A fixed-size vector with one value for each key in the domain.
+pub struct IndexVec<'a, K: IndexedValue + 'a, V, P: PointerFamily<'a>> {
+    vec: Vec<V>,
+    domain: P::Pointer<IndexedDomain<K>>,
+}
+
+/// [`IndexVec`] specialized to the [`RcFamily`].
+pub type RcIndexVec<'a, K, V> = IndexVec<'a, K, V, RcFamily>;
+
+/// [`IndexVec`] specialized to the [`ArcFamily`].
+pub type ArcIndexVec<'a, K, V> = IndexVec<'a, K, V, ArcFamily>;
+
+/// [`IndexVec`] specialized to the [`RefFamily`].
+pub type RefIndexVec<'a, K, V> = IndexVec<'a, K, V, RefFamily<'a>>;
+
+impl<'a, K, V, P> IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    /// Constructs a new vector where each key maps to a clone of `elem`.
+    pub fn from_elem(elem: V, domain: &P::Pointer<IndexedDomain<K>>) -> Self
+    where
+        V: Clone,
+    {
+        IndexVec {
+            vec: vec![elem; domain.len()],
+            domain: domain.clone(),
+        }
+    }
+
+    /// Constructs a new vector where each key maps to `f(key)`.
+    pub fn from_fn(f: impl FnMut(K::Index) -> V, domain: &P::Pointer<IndexedDomain<K>>) -> Self {
+        IndexVec {
+            vec: domain.indices().map(f).collect(),
+            domain: domain.clone(),
+        }
+    }
+
+    /// Constructs a new vector where each key maps to a clone of `V::default()`.
+    pub fn from_default(domain: &P::Pointer<IndexedDomain<K>>) -> Self
+    where
+        V: Default + Clone,
+    {
+        IndexVec::from_elem(V::default(), domain)
+    }
+
+    /// Constructs a new vector where each key maps to a fresh `V::default()`.
+    ///
+    /// Unlike [`IndexVec::from_default`], this does not require `V: Clone`.
+    pub fn from_default_fn(domain: &P::Pointer<IndexedDomain<K>>) -> Self
+    where
+        V: Default,
+    {
+        IndexVec::from_fn(|_| V::default(), domain)
+    }
+
+    /// Constructs a new vector from the entries of `map`, using a clone of `default`
+    /// for each key missing from `map`.
+    pub fn from_sparse_map(map: &SparseIndexMap<'a, K, V, P>, default: V) -> Self
+    where
+        V: Clone,
+    {
+        let mut vec = IndexVec::from_elem(default, &map.domain);
+        for (idx, value) in map {
+            vec.vec[idx.index()] = value.clone();
+        }
+        vec
+    }
+
+    /// Constructs a new vector from the entries of `map`, using a clone of `default`
+    /// for each key missing from `map`.
+    pub fn from_dense_map(map: &DenseIndexMap<'a, K, V, P>, default: V) -> Self
+    where
+        V: Clone,
+    {
+        IndexVec::from_fn(|idx| map.get(idx).unwrap_or(&default).clone(), &map.domain)
+    }
+
+    /// Extends `self` with clones of `fill` for each key added to the domain since `self`
+    /// was constructed.
+    ///
+    /// Call this after the domain grows and before accessing any of the new keys.
+    /// Otherwise, accessing a new key with [`IndexVec::get`] panics.
+    pub fn resize_to_domain(&mut self, fill: V)
+    where
+        V: Clone,
+    {
+        self.vec.resize(self.domain.len(), fill);
+    }
+
+    /// Returns an immutable reference to the value for a given key.
+    ///
+    /// Panics if the key is not within the domain. If the bounds check is too slow,
+    /// use the unsafe [`IndexVec::get_unchecked`].
+    #[inline]
+    pub fn get<M>(&self, idx: impl ToIndex<K, M>) -> &V {
+        let idx = idx.to_index(&self.domain).index();
+        match self.vec.get(idx) {
+            Some(value) => value,
+            None => out_of_domain(idx, self.vec.len()),
+        }
+    }
+
+    /// Returns a mutable reference to the value for a given key.
+    ///
+    /// Panics if the key is not within the domain. If the bounds check is too slow,
+    /// use the unsafe [`IndexVec::get_unchecked_mut`].
+    #[inline]
+    pub fn get_mut<M>(&mut self, idx: impl ToIndex<K, M>) -> &mut V {
+        let idx = idx.to_index(&self.domain).index();
+        let len = self.vec.len();
+        match self.vec.get_mut(idx) {
+            Some(value) => value,
+            None => out_of_domain(idx, len),
+        }
+    }
+
+    /// Returns a reference to the value for a given key.
+    ///
+    /// # Safety
+    /// This function has undefined behavior if `idx` is not within the domain.
+    #[inline]
+    pub unsafe fn get_unchecked<M>(&self, idx: impl ToIndex<K, M>) -> &V {
+        let idx = idx.to_index(&self.domain);
+        self.vec.get_unchecked(idx.index())
+    }
+
+    /// Returns a mutable reference to the value for a given key.
+    ///
+    /// # Safety
+    /// This function has undefined behavior if `idx` is not within the domain.
+    #[inline]
+    pub unsafe fn get_unchecked_mut<M>(&mut self, idx: impl ToIndex<K, M>) -> &mut V {
+        let idx = idx.to_index(&self.domain);
+        self.vec.get_unchecked_mut(idx.index())
+    }
+
+    /// Returns an immutable reference to the value for a given key, or `None` if the
+    /// key is outside the domain. Unlike [`IndexVec::get`], this does not panic.
+    #[inline]
+    pub fn try_get<M>(&self, idx: impl ToIndex<K, M>) -> Option<&V> {
+        self.vec.get(idx.to_index(&self.domain).index())
+    }
+
+    /// Returns a mutable reference to the value for a given key, or `None` if the
+    /// key is outside the domain. Unlike [`IndexVec::get_mut`], this does not panic.
+    #[inline]
+    pub fn try_get_mut<M>(&mut self, idx: impl ToIndex<K, M>) -> Option<&mut V> {
+        self.vec.get_mut(idx.to_index(&self.domain).index())
+    }
+
+    /// Swaps the values for keys `a` and `b`.
+    ///
+    /// Panics if either key is outside the domain.
+    #[inline]
+    pub fn swap<M1, M2>(&mut self, a: impl ToIndex<K, M1>, b: impl ToIndex<K, M2>) {
+        let a = a.to_index(&self.domain).index();
+        let b = b.to_index(&self.domain).index();
+        self.vec.swap(a, b);
+    }
+
+    /// Sets the value for every key to a clone of `value`.
+    #[inline]
+    pub fn fill(&mut self, value: V)
+    where
+        V: Clone,
+    {
+        self.vec.fill(value);
+    }
+
+    /// Returns an iterator over the values in index order.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.vec.iter()
+    }
+
+    /// Returns a mutable iterator over the values in index order.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator + '_ {
+        self.vec.iter_mut()
+    }
+
+    /// Constructs a new vector over the same domain where each key maps to `f` of its value in `self`.
+    pub fn map<W>(&self, f: impl FnMut(&V) -> W) -> IndexVec<'a, K, W, P> {
+        IndexVec {
+            vec: self.vec.iter().map(f).collect(),
+            domain: self.domain.clone(),
+        }
+    }
+
+    /// Like [`IndexVec::map`], but consumes `self` to pass each value to `f` by ownership.
+    pub fn into_map<W>(self, f: impl FnMut(V) -> W) -> IndexVec<'a, K, W, P> {
+        IndexVec {
+            vec: self.vec.into_iter().map(f).collect(),
+            domain: self.domain,
+        }
+    }
+
+    /// Calls `f` on each value in `self` with the value for the same key in `other`.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn zip_mut_with<W>(
+        &mut self,
+        other: &IndexVec<'a, K, W, P>,
+        mut f: impl FnMut(&mut V, &W),
+    ) {
+        debug_assert!(
+            P::ptr_eq(&self.domain, &other.domain),
+            "vectors do not share the same domain"
+        );
+        for (v, w) in self.vec.iter_mut().zip(&other.vec) {
+            f(v, w);
+        }
+    }
+
+    /// Returns an iterator over the keys in index order with the values for each key in `self` and `other`.
+    ///
+    /// `other` must have the same domain as `self`, which is only checked in debug builds.
+    pub fn zip<'s, W>(
+        &'s self,
+        other: &'s IndexVec<'a, K, W, P>,
+    ) -> impl DoubleEndedIterator<Item = (K::Index, &'s V, &'s W)> + ExactSizeIterator + Captures<'a> + 's
+    {
+        debug_assert!(
+            P::ptr_eq(&self.domain, &other.domain),
+            "vectors do not share the same domain"
+        );
+        self.vec
+            .iter()
+            .zip(&other.vec)
+            .enumerate()
+            .map(|(i, (v, w))| (K::Index::from_usize(i), v, w))
+    }
+
+    /// Returns a parallel iterator over the values in index order.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = &V> + '_
+    where
+        V: Sync,
+    {
+        use rayon::iter::IntoParallelRefIterator;
+        self.vec.par_iter()
+    }
+
+    /// Returns an iterator over the keys and values in index order.
+    #[inline]
+    pub fn iter_enumerated(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (K::Index, &V)> + ExactSizeIterator + '_ {
+        self.vec
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (K::Index::from_usize(i), v))
+    }
+
+    /// Returns a mutable iterator over the keys and values in index order.
+    #[inline]
+    pub fn iter_enumerated_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (K::Index, &mut V)> + ExactSizeIterator + '_ {
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .map(|(i, v)| (K::Index::from_usize(i), v))
+    }
+
+    /// Returns the values as a slice in index order.
+    #[inline]
+    pub fn as_slice(&self) -> &[V] {
+        &self.vec
+    }
+
+    /// Returns the values as a mutable slice in index order.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [V] {
+        &mut self.vec
+    }
+
+    /// Returns the [`IndexedDomain`] for the key type.
+    #[inline]
+    pub fn domain(&self) -> &P::Pointer<IndexedDomain<K>> {
+        &self.domain
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_domain(idx: usize, len: usize) -> ! {
+    panic!("index {idx} out of domain of length {len}")
+}
+
+impl<'a, K, V, P> Index<K::Index> for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: K::Index) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+impl<'a, K, V, P> IndexMut<K::Index> for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    #[inline]
+    fn index_mut(&mut self, index: K::Index) -> &mut Self::Output {
+        self.get_mut(index)
+    }
+}
+
+impl<'a, K, V, P> Clone for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: Clone,
+    P: PointerFamily<'a>,
+{
+    fn clone(&self) -> Self {
+        IndexVec {
+            vec: self.vec.clone(),
+            domain: self.domain.clone(),
+        }
+    }
+}
+
+impl<'a, K, V, P> PartialEq for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: PartialEq,
+    P: PointerFamily<'a>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+
+impl<'a, K, V, P> Eq for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: Eq,
+    P: PointerFamily<'a>,
+{
+}
+
+impl<'a, K, V, P> fmt::Debug for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    V: fmt::Debug,
+    P: PointerFamily<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, P> IntoIterator for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    type Item = V;
+    type IntoIter = std::vec::IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+
+/// Collects one value per key of the domain, in index order.
+///
+/// Panics if the iterator does not yield exactly as many values as there are keys in the domain.
+impl<'a, K, V, P> FromIndexicalIterator<'a, K, P, MarkerOwned, V> for IndexVec<'a, K, V, P>
+where
+    K: IndexedValue + 'a,
+    P: PointerFamily<'a>,
+{
+    fn from_indexical_iter(
+        iter: impl Iterator<Item = V>,
+        domain: &P::Pointer<IndexedDomain<K>>,
+    ) -> Self {
+        let vec = iter.collect::<Vec<_>>();
+        assert_eq!(
+            vec.len(),
+            domain.len(),
+            "number of values does not match the domain length"
+        );
+        IndexVec {
+            vec,
+            domain: domain.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IndexVec, RcIndexVec};
+    use crate::{
+        map::{DenseRcIndexMap, SparseRcIndexMap},
+        pointer::{CowFamily, CowPtr},
+        test_utils::StrIdx,
+        IndexedDomain, IndexicalIteratorExt,
+    };
+    use std::rc::Rc;
+
+    fn mk(s: &str) -> String {
+        s.to_string()
+    }
+
+    #[test]
+    fn test_indexvec_from_default() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+
+        let vec = RcIndexVec::<_, u32>::from_default(&d);
+        assert_eq!(vec.as_slice(), &[0, 0, 0]);
+
+        let mut vec = RcIndexVec::<_, Vec<u32>>::from_default_fn(&d);
+        vec.get_mut(mk("b")).push(1);
+        assert_eq!(vec.as_slice(), &[vec![], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_indexvec_from_maps() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+
+        let mut sparse = SparseRcIndexMap::new(&d);
+        sparse.insert(mk("b"), 5);
+        let vec = RcIndexVec::from_sparse_map(&sparse, 0);
+        assert_eq!(vec.as_slice(), &[0, 5, 0]);
+        assert_eq!(*vec.get(mk("b")), 5);
+
+        let mut dense = DenseRcIndexMap::new(&d);
+        dense.insert(mk("a"), 2);
+        dense.insert(mk("c"), 4);
+        let vec = RcIndexVec::from_dense_map(&dense, 0);
+        assert_eq!(vec.as_slice(), &[2, 0, 4]);
+    }
+
+    #[test]
+    fn test_indexvec_zip_mut_with() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut a = RcIndexVec::from_fn(|idx| idx.index() as u32, &d);
+        let b = RcIndexVec::from_elem(10u8, &d);
+        a.zip_mut_with(&b, |a, b| *a += *b as u32);
+        assert_eq!(a.as_slice(), &[10, 11, 12]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_indexvec_par_iter() {
+        use rayon::prelude::*;
+
+        let d = Rc::new(IndexedDomain::from_iter(
+            (0..1000usize).map(|i| i.to_string()),
+        ));
+        let vec = RcIndexVec::from_fn(|idx| idx.index(), &d);
+        assert_eq!(vec.par_iter().sum::<usize>(), vec.iter().sum::<usize>());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 7 out of domain of length 3")]
+    fn test_indexvec_get_out_of_domain() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let vec = RcIndexVec::from_fn(|idx| idx.index(), &d);
+        assert_eq!(*vec.get(mk("b")), 1);
+        assert_eq!(unsafe { *vec.get_unchecked(mk("c")) }, 2);
+        vec.get(StrIdx::from_usize(7));
+    }
+
+    #[test]
+    fn test_indexvec_try_get() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut vec = RcIndexVec::from_fn(|idx| idx.index(), &d);
+        assert_eq!(vec.try_get(mk("c")), Some(&2));
+        assert_eq!(vec.try_get(StrIdx::from_usize(3)), None);
+
+        *vec.try_get_mut(mk("a")).unwrap() = 5;
+        assert!(vec.try_get_mut(StrIdx::from_usize(3)).is_none());
+        assert_eq!(vec[StrIdx::from_usize(0)], 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of domain of length 3")]
+    fn test_indexvec_index_mut_out_of_domain() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut vec = RcIndexVec::from_fn(|idx| idx.index(), &d);
+        vec[StrIdx::from_usize(3)] = 0;
+    }
+
+    #[test]
+    fn test_indexvec_iter_enumerated() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut vec = RcIndexVec::from_fn(|idx| d.value(idx).clone(), &d);
+        for (idx, value) in vec.iter_enumerated() {
+            assert_eq!(d.value(idx), value);
+        }
+
+        for (idx, value) in vec.iter_enumerated_mut() {
+            value.push_str(&idx.index().to_string());
+        }
+        assert_eq!(vec.as_slice(), &[mk("a0"), mk("b1"), mk("c2")]);
+        assert_eq!(
+            vec.iter_enumerated().next_back(),
+            Some((d.index(&mk("c")), &mk("c2")))
+        );
+    }
+
+    #[test]
+    fn test_indexvec_map() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("bb"), mk("cccc")]));
+        let lens = RcIndexVec::from_fn(|idx| d.value(idx).len() as u32, &d);
+        let even = lens.map(|len| len % 2 == 0);
+        for (idx, is_even) in even.iter_enumerated() {
+            assert_eq!(*is_even, d.value(idx).len() % 2 == 0);
+        }
+        assert_eq!(even.as_slice(), &[false, true, true]);
+
+        let strs = lens.into_map(|len| len.to_string());
+        assert_eq!(strs.as_slice(), &[mk("1"), mk("2"), mk("4")]);
+    }
+
+    #[test]
+    fn test_indexvec_resize_to_domain() {
+        let base = IndexedDomain::from_iter([mk("a"), mk("b")]);
+        let mut vec = IndexVec::<_, _, CowFamily>::from_elem(0, &CowPtr::from(&base));
+        let c = vec.domain.to_mut().insert(mk("c"));
+        assert_eq!(vec.as_slice().len(), 2);
+        assert!(vec.try_get(c).is_none());
+
+        vec.resize_to_domain(7);
+        assert_eq!(*vec.get(c), 7);
+        assert_eq!(vec.as_slice(), &[0, 0, 7]);
+    }
+
+    #[test]
+    fn test_indexvec_swap_fill() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b"), mk("c")]));
+        let mut vec = RcIndexVec::from_fn(|idx| idx.index(), &d);
+        vec.swap(mk("a"), StrIdx::from_usize(2));
+        assert_eq!(vec.as_slice(), &[2, 1, 0]);
+
+        vec.fill(5);
+        assert_eq!(vec.as_slice(), &[5, 5, 5]);
+    }
+
+    #[test]
+    fn test_indexvec_zip() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let names = RcIndexVec::from_fn(|idx| d.value(idx).to_uppercase(), &d);
+        let counts = RcIndexVec::from_fn(|idx| idx.index() * 10, &d);
+        let zipped = names.zip(&counts).collect::<Vec<_>>();
+        assert_eq!(
+            zipped,
+            vec![
+                (StrIdx::from_usize(0), &mk("A"), &0),
+                (StrIdx::from_usize(1), &mk("B"), &10)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indexvec_into_iter() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let vec = RcIndexVec::from_fn(|idx| d.value(idx).clone(), &d);
+        assert_eq!(vec.into_iter().collect::<Vec<_>>(), vec![mk("a"), mk("b")]);
+    }
+
+    #[test]
+    fn test_indexvec_collect_indexical() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let vec: RcIndexVec<_, _> = [3, 4].into_iter().collect_indexical(&d);
+        assert_eq!(*vec.get(mk("b")), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match the domain length")]
+    fn test_indexvec_collect_indexical_mismatch() {
+        let d = Rc::new(IndexedDomain::from_iter([mk("a"), mk("b")]));
+        let _: RcIndexVec<_, _> = [3].into_iter().collect_indexical(&d);
+    }
+}